@@ -0,0 +1,248 @@
+use chrono::Utc;
+use sea_orm::{ActiveValue, ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommandOption, CreateEmbed,
+    CreateMessage, EditInteractionResponse, ResolvedOption, ResolvedValue,
+};
+use serenity::builder::CreateCommand;
+
+use crate::entities::actions::ActionType;
+use crate::entities::{bounties, prelude::*};
+use crate::utils::config::RuleSubject;
+use crate::utils::issues::{GithubTarget, REPO_NAME, REPO_OWNER};
+use crate::utils::permissions::check_senate_member;
+use crate::utils::validation::check_eligibility;
+use crate::{CONFIG, Handler};
+
+pub async fn run(
+    h: &Handler,
+    ctx: &Context,
+    command: &CommandInteraction,
+) -> Result<(), serenity::Error> {
+    command.defer_ephemeral(&ctx.http).await?;
+    match command.data.options().first() {
+        Some(ResolvedOption {
+            name: "add",
+            value: ResolvedValue::SubCommand(options),
+            ..
+        }) => {
+            if !check_senate_member(ctx, command).await? {
+                return Ok(());
+            }
+            add(h, ctx, command, options).await
+        }
+        Some(ResolvedOption { name: "list", .. }) => list(h, ctx, command).await,
+        _ => Err(serenity::Error::Other("Invalid input")),
+    }
+}
+
+async fn add(
+    h: &Handler,
+    ctx: &Context,
+    command: &CommandInteraction,
+    options: &[ResolvedOption<'_>],
+) -> Result<(), serenity::Error> {
+    let mut issue_input = None;
+    let mut points = None;
+    let mut expiry_days = None;
+    for option in options {
+        match (option.name, &option.value) {
+            ("issue", ResolvedValue::String(s)) => issue_input = Some(*s),
+            ("points", ResolvedValue::Integer(i)) => points = Some(*i),
+            ("expiry", ResolvedValue::Integer(i)) => expiry_days = Some(*i),
+            _ => (),
+        }
+    }
+    let (Some(issue_input), Some(points)) = (issue_input, points) else {
+        return Err(serenity::Error::Other("Invalid input"));
+    };
+
+    let target = match GithubTarget::from_url(issue_input) {
+        Some(target @ (GithubTarget::Issue { .. } | GithubTarget::Reference { .. })) => target,
+        _ => {
+            command
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new()
+                        .content("Bounties can only be put on issues (URL or `#number`) !"),
+                )
+                .await?;
+            return Ok(());
+        }
+    };
+    let issue = match octocrab::instance()
+        .issues(REPO_OWNER, REPO_NAME)
+        .get(target.issue_number())
+        .await
+    {
+        Ok(issue) if issue.pull_request.is_none() => issue,
+        _ => {
+            command
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new()
+                        .content(format!("Couldn't find issue #{}", target.issue_number())),
+                )
+                .await?;
+            return Ok(());
+        }
+    };
+
+    // Claimed bounties go to the PR closing the issue, which has to be eligible for the contest
+    if let Err(reason) = check_eligibility(
+        ActionType::PRFix,
+        RuleSubject::LinkedIssue,
+        &issue,
+        &issue.user.login,
+        None,
+    ) {
+        command
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content(format!(
+                    "Fixing #{} can't count for the contest, so it can't have a bounty. {reason}",
+                    issue.number
+                )),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let now = Utc::now().timestamp();
+    let expires_at = expiry_days.map(|days| now + days * 24 * 60 * 60);
+    let bounty = bounties::ActiveModel {
+        id: ActiveValue::NotSet,
+        issue_number: ActiveValue::Set(issue.number),
+        points: ActiveValue::Set(points),
+        created_by: ActiveValue::Set(command.user.id.get().to_string()),
+        created_at: ActiveValue::Set(now),
+        expires_at: ActiveValue::Set(expires_at),
+        claimed_by: ActiveValue::Set(None),
+        claimed_action: ActiveValue::Set(None),
+        claimed_at: ActiveValue::Set(None),
+    };
+    if let Err(e) = Bounties::insert(bounty).exec(&h.db_conn).await {
+        log::error!("Error while creating bounty: {e:?}");
+        return Err(serenity::Error::Other("Error while creating bounty"));
+    }
+
+    let description = format!(
+        "### 💰 New bounty: +{} points for fixing [#{} - {}]({}){}",
+        points,
+        issue.number,
+        issue.title,
+        issue.html_url,
+        match expires_at {
+            Some(expiry) => format!("\nExpires <t:{expiry}:R>"),
+            None => String::new(),
+        }
+    );
+    CONFIG
+        .feed_channel
+        .send_message(
+            &ctx.http,
+            CreateMessage::new().embed(CreateEmbed::new().description(&description)),
+        )
+        .await?;
+    command
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new().content("Created the bounty !"),
+        )
+        .await?;
+    Ok(())
+}
+
+async fn list(
+    h: &Handler,
+    ctx: &Context,
+    command: &CommandInteraction,
+) -> Result<(), serenity::Error> {
+    let now = Utc::now().timestamp();
+    let bounties = match Bounties::find()
+        .filter(bounties::Column::ClaimedBy.is_null())
+        .order_by_desc(bounties::Column::Points)
+        .all(&h.db_conn)
+        .await
+    {
+        Ok(b) => b,
+        Err(e) => {
+            log::error!("Error while fetching bounties: {e:?}");
+            return Err(serenity::Error::Other("Error while fetching bounties"));
+        }
+    };
+
+    let lines: Vec<String> = bounties
+        .iter()
+        .filter(|bounty| bounty.is_open(now))
+        .map(|bounty| {
+            format!(
+                "**+{}** [#{}](https://github.com/{REPO_OWNER}/{REPO_NAME}/issues/{}){}",
+                bounty.points,
+                bounty.issue_number,
+                bounty.issue_number,
+                match bounty.expires_at {
+                    Some(expiry) => format!(" - expires <t:{expiry}:R>"),
+                    None => String::new(),
+                }
+            )
+        })
+        .collect();
+    command
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new().embed(
+                CreateEmbed::new()
+                    .title("Open bounties")
+                    .description(if lines.is_empty() {
+                        "No open bounty right now".to_string()
+                    } else {
+                        lines.join("\n")
+                    }),
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("bounty")
+        .description("Bonus points for fixing specific issues")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "add",
+                "Put a bounty on an issue",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "issue",
+                    "The url (or #number) of the issue",
+                )
+                .required(true),
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Integer,
+                    "points",
+                    "The bonus points awarded to the first confirmed fix",
+                )
+                .min_int_value(1)
+                .required(true),
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Integer,
+                    "expiry",
+                    "Number of days after which the bounty expires",
+                )
+                .min_int_value(1),
+            ),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "list",
+            "See the open bounties",
+        ))
+}
@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommandOption, CreateEmbed,
+    EditInteractionResponse, ResolvedValue, UserId,
+};
+use serenity::builder::CreateCommand;
+
+use crate::Handler;
+use crate::utils::contestants::{load_display_names, user_label};
+use crate::utils::ui::{CATEGORIES, category_title, category_unit, compute_scores, rank_users};
+
+pub async fn run(
+    h: &Handler,
+    ctx: &Context,
+    command: &CommandInteraction,
+) -> Result<(), serenity::Error> {
+    command.defer_ephemeral(&ctx.http).await?;
+
+    let mut users: Vec<UserId> = vec![];
+    for option in command.data.options() {
+        if let ("a" | "b", ResolvedValue::User(u, _)) = (option.name, &option.value) {
+            users.push(u.id);
+        }
+    }
+    if users.len() != 2 {
+        return Err(serenity::Error::Other("Invalid input"));
+    }
+
+    let score_map = match compute_scores(&h.db_conn).await {
+        Ok(score_map) => score_map,
+        Err(e) => {
+            log::error!("Error while computing scores: {e:?}");
+            return Err(serenity::Error::Other("Error while computing scores"));
+        }
+    };
+    let display_names = load_display_names(&h.db_conn).await.unwrap_or_else(|e| {
+        log::error!("Error while fetching contestant display names: {e:?}");
+        HashMap::new()
+    });
+    let ranks: Vec<_> = CATEGORIES
+        .into_iter()
+        .map(|action_type| (action_type, rank_users(&score_map, action_type)))
+        .collect();
+
+    let mut embed = CreateEmbed::new().title("Comparison");
+    for user in users {
+        let user_id = user.get().to_string();
+        let score = score_map.get(&user_id).copied().unwrap_or_default();
+        let mut field = String::new();
+        for (action_type, ranked) in &ranks {
+            let rank = ranked
+                .iter()
+                .find(|(id, _, _)| *id == user_id)
+                .map_or("Not ranked".to_string(), |(_, rank, _)| format!("#{rank}"));
+            field.push_str(&format!(
+                "**{}**\n{} {}{} - {rank}\n",
+                category_title(*action_type),
+                score.get_category_value(*action_type),
+                category_unit(*action_type),
+                if action_type.is_none() {
+                    score.get_points_breakdown()
+                } else {
+                    String::new()
+                },
+            ));
+        }
+        embed = embed.field(user_label(&display_names, &user_id), field, true);
+    }
+
+    command
+        .edit_response(&ctx.http, EditInteractionResponse::new().embed(embed))
+        .await?;
+    Ok(())
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("compare")
+        .description("Compare the scores of two users")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::User, "a", "The first user").required(true),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::User, "b", "The second user")
+                .required(true),
+        )
+}
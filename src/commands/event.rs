@@ -0,0 +1,348 @@
+use chrono::Utc;
+use sea_orm::{ActiveValue, EntityTrait, QueryOrder};
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommandOption, CreateEmbed,
+    CreateMessage, EditInteractionResponse, ResolvedOption, ResolvedValue,
+};
+use serenity::builder::CreateCommand;
+
+use crate::entities::actions::ActionType;
+use crate::entities::{multiplier_events, prelude::*};
+use crate::utils::permissions::check_senate_member;
+use crate::utils::time::parse_datetime;
+use crate::{CONFIG, Handler};
+
+pub async fn run(
+    h: &Handler,
+    ctx: &Context,
+    command: &CommandInteraction,
+) -> Result<(), serenity::Error> {
+    command.defer_ephemeral(&ctx.http).await?;
+    match command.data.options().first() {
+        Some(ResolvedOption {
+            name: "schedule",
+            value: ResolvedValue::SubCommand(options),
+            ..
+        }) => {
+            if !check_senate_member(ctx, command).await? {
+                return Ok(());
+            }
+            schedule(h, ctx, command, options).await
+        }
+        Some(ResolvedOption {
+            name: "cancel",
+            value: ResolvedValue::SubCommand(options),
+            ..
+        }) => {
+            if !check_senate_member(ctx, command).await? {
+                return Ok(());
+            }
+            cancel(h, ctx, command, options).await
+        }
+        Some(ResolvedOption { name: "list", .. }) => list(h, ctx, command).await,
+        _ => Err(serenity::Error::Other("Invalid input")),
+    }
+}
+
+fn describe_event(event: &multiplier_events::Model) -> String {
+    format!(
+        "**x{}** {} (#{}): <t:{}:f> - <t:{}:f>{}",
+        event.factor,
+        event.name,
+        event.id,
+        event.starts_at,
+        event.ends_at,
+        match event.action_type {
+            Some(t) => format!(", {t} only"),
+            None => String::new(),
+        }
+    )
+}
+
+async fn schedule(
+    h: &Handler,
+    ctx: &Context,
+    command: &CommandInteraction,
+    options: &[ResolvedOption<'_>],
+) -> Result<(), serenity::Error> {
+    let mut name = None;
+    let mut start = None;
+    let mut end = None;
+    let mut factor = None;
+    let mut action_type = None;
+    for option in options {
+        match (option.name, &option.value) {
+            ("name", ResolvedValue::String(s)) => name = Some(*s),
+            ("start", ResolvedValue::String(s)) => start = Some(*s),
+            ("end", ResolvedValue::String(s)) => end = Some(*s),
+            ("factor", ResolvedValue::Number(n)) => factor = Some(*n),
+            ("type", ResolvedValue::String(s)) => {
+                action_type = Some(match *s {
+                    "bug_report" => ActionType::ReportBug,
+                    "bug_confirm" => ActionType::ConfirmBug,
+                    _ => ActionType::PRFix,
+                })
+            }
+            _ => (),
+        }
+    }
+    let (Some(name), Some(start), Some(end), Some(factor)) = (name, start, end, factor) else {
+        return Err(serenity::Error::Other("Invalid input"));
+    };
+
+    let (Some(starts_at), Some(ends_at)) = (parse_datetime(start), parse_datetime(end)) else {
+        command
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content(
+                    "Invalid date, use a unix timestamp or the `YYYY-MM-DD HH:MM` format (UTC)",
+                ),
+            )
+            .await?;
+        return Ok(());
+    };
+    if ends_at <= starts_at {
+        command
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("The event must end after it starts !"),
+            )
+            .await?;
+        return Ok(());
+    }
+    // Events reward activity, a factor below 1 would take points away instead
+    if factor < 1.0 {
+        command
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("The factor must be at least 1 !"),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let event = multiplier_events::ActiveModel {
+        id: ActiveValue::NotSet,
+        name: ActiveValue::Set(name.to_string()),
+        starts_at: ActiveValue::Set(starts_at.timestamp()),
+        ends_at: ActiveValue::Set(ends_at.timestamp()),
+        factor: ActiveValue::Set(factor),
+        action_type: ActiveValue::Set(action_type),
+        created_by: ActiveValue::Set(command.user.id.get().to_string()),
+        created_at: ActiveValue::Set(Utc::now().timestamp()),
+        cancelled_by: ActiveValue::Set(None),
+        cancelled_at: ActiveValue::Set(None),
+    };
+    let event = match MultiplierEvents::insert(event)
+        .exec_with_returning(&h.db_conn)
+        .await
+    {
+        Ok(event) => event,
+        Err(e) => {
+            log::error!("Error while scheduling multiplier event: {e:?}");
+            return Err(serenity::Error::Other("Error while scheduling event"));
+        }
+    };
+
+    CONFIG
+        .feed_channel
+        .send_message(
+            &ctx.http,
+            CreateMessage::new().embed(CreateEmbed::new().description(format!(
+                "### ⏫ Points multiplier event scheduled !\n{}",
+                describe_event(&event)
+            ))),
+        )
+        .await?;
+    command
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new().content(format!("Scheduled {}", describe_event(&event))),
+        )
+        .await?;
+    Ok(())
+}
+
+async fn cancel(
+    h: &Handler,
+    ctx: &Context,
+    command: &CommandInteraction,
+    options: &[ResolvedOption<'_>],
+) -> Result<(), serenity::Error> {
+    let Some(ResolvedOption {
+        value: ResolvedValue::Integer(id),
+        ..
+    }) = options.first()
+    else {
+        return Err(serenity::Error::Other("Invalid input"));
+    };
+
+    let event = match MultiplierEvents::find_by_id(*id as u32)
+        .one(&h.db_conn)
+        .await
+    {
+        Ok(Some(event)) if event.cancelled_at.is_none() => event,
+        _ => {
+            command
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new()
+                        .content(format!("No active multiplier event with id {id}")),
+                )
+                .await?;
+            return Ok(());
+        }
+    };
+
+    // Events are kept once cancelled, so that the points they gave can still be audited
+    let model = multiplier_events::ActiveModel {
+        id: ActiveValue::Set(event.id),
+        cancelled_by: ActiveValue::Set(Some(command.user.id.get().to_string())),
+        cancelled_at: ActiveValue::Set(Some(Utc::now().timestamp())),
+        ..Default::default()
+    };
+    if let Err(e) = MultiplierEvents::update(model).exec(&h.db_conn).await {
+        log::error!(
+            "Error while cancelling multiplier event {}: {e:?}",
+            event.id
+        );
+        return Err(serenity::Error::Other("Error while cancelling event"));
+    }
+    command
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new().content(format!("Cancelled {}", describe_event(&event))),
+        )
+        .await?;
+    Ok(())
+}
+
+async fn list(
+    h: &Handler,
+    ctx: &Context,
+    command: &CommandInteraction,
+) -> Result<(), serenity::Error> {
+    let events = match MultiplierEvents::find()
+        .order_by_desc(multiplier_events::Column::StartsAt)
+        .all(&h.db_conn)
+        .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            log::error!("Error while fetching multiplier events: {e:?}");
+            return Err(serenity::Error::Other("Error while fetching events"));
+        }
+    };
+
+    let now = Utc::now().timestamp();
+    let mut upcoming = vec![];
+    let mut active = vec![];
+    let mut past = vec![];
+    for event in &events {
+        let line = describe_event(event);
+        match event.cancelled_at {
+            Some(cancelled_at) => past.push(format!(
+                "~~{line}~~ cancelled by <@{}> <t:{cancelled_at}:R>",
+                event.cancelled_by.as_deref().unwrap_or_default()
+            )),
+            None if now < event.starts_at => upcoming.push(line),
+            None if now < event.ends_at => active.push(line),
+            None => past.push(line),
+        }
+    }
+
+    let section = |lines: Vec<String>| {
+        if lines.is_empty() {
+            "\nNone".to_string()
+        } else {
+            lines.iter().map(|l| format!("\n{l}")).collect()
+        }
+    };
+    let mut description = format!(
+        "**Active**{}\n\n**Upcoming**{}\n\n**Past**{}",
+        section(active),
+        section(upcoming),
+        section(past)
+    );
+    if description.chars().count() > 4096 {
+        description = description.chars().take(4095).collect::<String>() + "…";
+    }
+    command
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new().embed(
+                CreateEmbed::new()
+                    .title("Points multiplier events")
+                    .description(description),
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("event")
+        .description("Time-limited points multiplier events")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "schedule",
+                "Schedule a points multiplier event",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(CommandOptionType::String, "name", "Name of the event")
+                    .required(true),
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "start",
+                    "Start of the event (YYYY-MM-DD HH:MM in UTC, or a unix timestamp)",
+                )
+                .required(true),
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "end",
+                    "End of the event (YYYY-MM-DD HH:MM in UTC, or a unix timestamp)",
+                )
+                .required(true),
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Number,
+                    "factor",
+                    "The points multiplier, e.g. 2 for double points",
+                )
+                .min_number_value(1.0)
+                .required(true),
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "type",
+                    "Only multiply this type of submission",
+                )
+                .add_string_choice("Discover Bug", "bug_report")
+                .add_string_choice("Confirm Bug", "bug_confirm")
+                .add_string_choice("Solve Bug", "fix_pr"),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "cancel",
+                "Cancel a points multiplier event",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(CommandOptionType::Integer, "id", "Id of the event")
+                    .required(true),
+            ),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "list",
+            "See the points multiplier events",
+        ))
+}
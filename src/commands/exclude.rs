@@ -0,0 +1,109 @@
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{ActiveValue, EntityTrait};
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommandOption, EditInteractionResponse,
+    ResolvedValue,
+};
+use serenity::builder::CreateCommand;
+
+use crate::entities::{contestants, prelude::*};
+use crate::utils::permissions::check_senate_member;
+use crate::utils::point_roles::sync_point_roles;
+use crate::{CONFIG, Handler};
+
+pub async fn run(
+    h: &Handler,
+    ctx: &Context,
+    command: &CommandInteraction,
+) -> Result<(), serenity::Error> {
+    command.defer_ephemeral(&ctx.http).await?;
+    if !check_senate_member(ctx, command).await? {
+        return Ok(());
+    }
+
+    let mut user = None;
+    let mut reinstate = false;
+    for option in command.data.options() {
+        match (option.name, &option.value) {
+            ("user", ResolvedValue::User(u, _)) => user = Some(u.id),
+            ("reinstate", ResolvedValue::Boolean(b)) => reinstate = *b,
+            _ => (),
+        }
+    }
+    let Some(user) = user else {
+        return Err(serenity::Error::Other("Invalid input"));
+    };
+
+    // Users excluded before joining get a record without accepted rules
+    let contestant = contestants::ActiveModel {
+        user_id: ActiveValue::Set(user.get().to_string()),
+        display_name: ActiveValue::Set(None),
+        accepted_at: ActiveValue::Set(None),
+        excluded: ActiveValue::Set(!reinstate),
+    };
+    if let Err(e) = Contestants::insert(contestant)
+        .on_conflict(
+            OnConflict::column(contestants::Column::UserId)
+                .update_column(contestants::Column::Excluded)
+                .to_owned(),
+        )
+        .exec(&h.db_conn)
+        .await
+    {
+        log::error!("Error while excluding {user}: {e:?}");
+        return Err(serenity::Error::Other("Error while excluding contestant"));
+    }
+    sync_point_roles(&h.db_conn, ctx, command.guild_id, &[user.get().to_string()]).await;
+    // Only contestants who joined had the role, and get it back when reinstated
+    let joined = match Contestants::find_by_id(user.get().to_string())
+        .one(&h.db_conn)
+        .await
+    {
+        Ok(contestant) => contestant.is_some_and(|c| c.accepted_at.is_some()),
+        Err(e) => {
+            log::error!("Error while fetching contestant {user}: {e:?}");
+            false
+        }
+    };
+    if let (Some(role_id), Some(guild_id)) = (CONFIG.contestant_role, command.guild_id) {
+        let res = if !reinstate {
+            ctx.http
+                .remove_member_role(guild_id, user, role_id, Some("Excluded from the contest"))
+                .await
+        } else if joined {
+            ctx.http
+                .add_member_role(guild_id, user, role_id, Some("Reinstated in the contest"))
+                .await
+        } else {
+            Ok(())
+        };
+        if let Err(e) = res {
+            log::error!("Error while updating contestant role of {user}: {e:?}");
+        }
+    }
+    command
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new().content(if reinstate {
+                format!("<@{user}> can take part in the contest again")
+            } else {
+                format!("Excluded <@{user}> from the contest and its leaderboards")
+            }),
+        )
+        .await?;
+    Ok(())
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("exclude")
+        .description("Exclude a user from the contest")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::User, "user", "The user to exclude")
+                .required(true),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            "reinstate",
+            "Allow an excluded user to take part again instead",
+        ))
+}
@@ -0,0 +1,123 @@
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommandOption, EditInteractionResponse,
+    ResolvedValue,
+};
+use serenity::builder::CreateCommand;
+
+use crate::Handler;
+use crate::entities::actions::{ActionStatus, ActionType};
+use crate::utils::export::{ExportFilters, ExportFormat, export_tables};
+use crate::utils::permissions::check_senate_member;
+use crate::utils::time::parse_datetime;
+
+pub async fn run(
+    h: &Handler,
+    ctx: &Context,
+    command: &CommandInteraction,
+) -> Result<(), serenity::Error> {
+    command.defer_ephemeral(&ctx.http).await?;
+    if !check_senate_member(ctx, command).await? {
+        return Ok(());
+    }
+
+    let mut format = ExportFormat::Csv;
+    let mut filters = ExportFilters::default();
+    let (mut from, mut to) = (None, None);
+    for option in command.data.options() {
+        match (option.name, &option.value) {
+            ("format", ResolvedValue::String("json")) => format = ExportFormat::Json,
+            ("status", ResolvedValue::String(s)) => {
+                filters.status = Some(match *s {
+                    "pending" => ActionStatus::Pending,
+                    "confirmed" => ActionStatus::Confirmed,
+                    "denied" => ActionStatus::Denied,
+                    _ => continue,
+                })
+            }
+            ("type", ResolvedValue::String(s)) => {
+                filters.action_type = Some(match *s {
+                    "bug_report" => ActionType::ReportBug,
+                    "bug_confirm" => ActionType::ConfirmBug,
+                    "pr_fix" => ActionType::PRFix,
+                    _ => continue,
+                })
+            }
+            ("from", ResolvedValue::String(s)) => from = Some(*s),
+            ("to", ResolvedValue::String(s)) => to = Some(*s),
+            _ => (),
+        }
+    }
+    if from.is_some() || to.is_some() {
+        let parse = |input: Option<&str>, default: i64| match input {
+            Some(input) => parse_datetime(input).map(|date| date.timestamp()),
+            None => Some(default),
+        };
+        let (Some(start), Some(end)) = (parse(from, i64::MIN), parse(to, i64::MAX)) else {
+            command
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new().content(
+                        "Invalid date, use a unix timestamp or the `YYYY-MM-DD HH:MM` format (UTC)",
+                    ),
+                )
+                .await?;
+            return Ok(());
+        };
+        filters.bounds = Some((start, end));
+    }
+
+    let tables = match export_tables(&h.db_conn, ctx, &filters).await {
+        Ok(tables) => tables,
+        Err(e) => {
+            log::error!("Error while exporting contest data: {e:?}");
+            return Err(serenity::Error::Other("Error while exporting contest data"));
+        }
+    };
+    let mut builder =
+        EditInteractionResponse::new().content("Here are the submissions, scores and rankings");
+    for table in tables {
+        builder = builder.new_attachment(table.into_attachment(format));
+    }
+    command.edit_response(&ctx.http, builder).await?;
+    Ok(())
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("export")
+        .description("Export the submissions, scores and rankings of the contest")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "format", "The file format")
+                .add_string_choice("CSV", "csv")
+                .add_string_choice("JSON", "json"),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "status",
+                "Only export submissions with this status",
+            )
+            .add_string_choice("Pending", "pending")
+            .add_string_choice("Confirmed", "confirmed")
+            .add_string_choice("Denied", "denied"),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "type",
+                "Only export this type of submission",
+            )
+            .add_string_choice("Discover Bug", "bug_report")
+            .add_string_choice("Confirm Bug", "bug_confirm")
+            .add_string_choice("Solve Bug", "pr_fix"),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::String,
+            "from",
+            "Only count items created on GitHub from this date (YYYY-MM-DD HH:MM in UTC)",
+        ))
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::String,
+            "to",
+            "Only count items created on GitHub before this date (YYYY-MM-DD HH:MM in UTC)",
+        ))
+}
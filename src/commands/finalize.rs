@@ -0,0 +1,39 @@
+use serenity::all::{CommandInteraction, Context, EditInteractionResponse};
+use serenity::builder::CreateCommand;
+
+use crate::Handler;
+use crate::utils::permissions::check_senate_member;
+use crate::utils::phase::ContestPhase;
+use crate::utils::results::finalize_results;
+
+pub async fn run(
+    h: &Handler,
+    ctx: &Context,
+    command: &CommandInteraction,
+) -> Result<(), serenity::Error> {
+    command.defer_ephemeral(&ctx.http).await?;
+    if !check_senate_member(ctx, command).await? {
+        return Ok(());
+    }
+
+    let content = if ContestPhase::current() != ContestPhase::Finalized {
+        "The contest isn't finalized yet !".to_string()
+    } else {
+        match finalize_results(&h.db_conn, ctx).await {
+            Ok(()) => "Announced the corrected results and updated the winner roles".to_string(),
+            Err(e) => {
+                log::error!("{e}");
+                return Err(serenity::Error::Other("Error while finalizing results"));
+            }
+        }
+    };
+    command
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await?;
+    Ok(())
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("finalize")
+        .description("Announce the results again and reassign the winner roles after corrections")
+}
@@ -0,0 +1,187 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{ActiveValue, EntityTrait};
+use serenity::all::{
+    ButtonStyle, CommandInteraction, CommandOptionType, Context, CreateButton, CreateCommandOption,
+    CreateEmbed, EditInteractionResponse, EditMessage, ResolvedOption, ResolvedValue,
+};
+use serenity::builder::CreateCommand;
+
+use crate::entities::{contestants, prelude::*};
+use crate::utils::ui::escape_markdown;
+use crate::{CONFIG, Handler};
+
+pub async fn run(
+    h: &Handler,
+    ctx: &Context,
+    command: &CommandInteraction,
+) -> Result<(), serenity::Error> {
+    command.defer_ephemeral(&ctx.http).await?;
+    let display_name = match command.data.options().first() {
+        Some(ResolvedOption {
+            value: ResolvedValue::String(name),
+            ..
+        }) if !name.trim().is_empty() => Some(name.trim().to_string()),
+        _ => None,
+    };
+    let user_id = command.user.id.get().to_string();
+
+    let existing = match Contestants::find_by_id(&user_id).one(&h.db_conn).await {
+        Ok(contestant) => contestant,
+        Err(e) => {
+            log::error!("Error while fetching contestant {user_id}: {e:?}");
+            return Err(serenity::Error::Other("Error while fetching contestant"));
+        }
+    };
+    match existing {
+        Some(contestant) if contestant.excluded => {
+            command
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new().content("You were excluded from the contest"),
+                )
+                .await?;
+            return Ok(());
+        }
+        Some(contestant) if contestant.accepted_at.is_some() => {
+            let content = match display_name {
+                Some(name) => {
+                    let model = contestants::ActiveModel {
+                        user_id: ActiveValue::Set(user_id),
+                        display_name: ActiveValue::Set(Some(name.clone())),
+                        ..Default::default()
+                    };
+                    if let Err(e) = Contestants::update(model).exec(&h.db_conn).await {
+                        log::error!("Error while updating display name: {e:?}");
+                        return Err(serenity::Error::Other("Error while updating display name"));
+                    }
+                    format!(
+                        "You already joined the contest, your display name is now **{}**",
+                        escape_markdown(&name)
+                    )
+                }
+                None => "You already joined the contest !".to_string(),
+            };
+            grant_contestant_role(ctx, command).await;
+            command
+                .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+                .await?;
+            return Ok(());
+        }
+        _ => (),
+    }
+
+    command
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new()
+                .embed(
+                    CreateEmbed::new()
+                        .title("Bug Catching Contest 2025 Rules")
+                        .description(&CONFIG.contest_rules),
+                )
+                .button(
+                    CreateButton::new("ignore-join-accept")
+                        .style(ButtonStyle::Success)
+                        .label("Accept"),
+                )
+                .button(
+                    CreateButton::new("ignore-join-decline")
+                        .style(ButtonStyle::Danger)
+                        .label("Decline"),
+                ),
+        )
+        .await?;
+    let mut msg = command.get_response(&ctx.http).await?;
+    let btn_interaction = msg
+        .await_component_interaction(ctx)
+        .timeout(Duration::from_secs(3 * 60))
+        .await;
+    match btn_interaction {
+        Some(i) => {
+            i.defer(&ctx.http).await?;
+            let content = if i.data.custom_id == "ignore-join-accept" {
+                let contestant = contestants::ActiveModel {
+                    user_id: ActiveValue::Set(user_id),
+                    display_name: ActiveValue::Set(display_name),
+                    accepted_at: ActiveValue::Set(Some(Utc::now().timestamp())),
+                    excluded: ActiveValue::Set(false),
+                };
+                if let Err(e) = Contestants::insert(contestant)
+                    .on_conflict(
+                        OnConflict::column(contestants::Column::UserId)
+                            .update_columns([
+                                contestants::Column::DisplayName,
+                                contestants::Column::AcceptedAt,
+                            ])
+                            .to_owned(),
+                    )
+                    .exec(&h.db_conn)
+                    .await
+                {
+                    log::error!("Error while registering contestant: {e:?}");
+                    return Err(serenity::Error::Other("Error while registering contestant"));
+                }
+                grant_contestant_role(ctx, command).await;
+                "Welcome to the contest ! Use `/submit` to submit your findings"
+            } else {
+                "You must accept the rules to join the contest"
+            };
+            command
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new()
+                        .content(content)
+                        .components(vec![])
+                        .embeds(vec![]),
+                )
+                .await?;
+        }
+        None => {
+            msg.edit(
+                &ctx.http,
+                EditMessage::new().content("Interaction timed out..."),
+            )
+            .await?;
+            msg.components.clear();
+        }
+    }
+    Ok(())
+}
+
+/// Gives the configured contestant role to the author of the command, if any
+async fn grant_contestant_role(ctx: &Context, command: &CommandInteraction) {
+    let (Some(role_id), Some(guild_id)) = (CONFIG.contestant_role, command.guild_id) else {
+        return;
+    };
+    if let Err(e) = ctx
+        .http
+        .add_member_role(
+            guild_id,
+            command.user.id,
+            role_id,
+            Some("Joined the contest"),
+        )
+        .await
+    {
+        log::error!(
+            "Error while granting contestant role to {}: {e:?}",
+            command.user.id
+        );
+    }
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("join")
+        .description("Join the contest")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "display_name",
+                "Name displayed next to your mention on the leaderboards",
+            )
+            .max_length(32),
+        )
+}
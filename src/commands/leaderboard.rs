@@ -1,64 +1,299 @@
 use serenity::all::{
-    CommandInteraction, Context, CreateInteractionResponse, CreateInteractionResponseMessage,
-    CreateSelectMenu, CreateSelectMenuOption,
+    ButtonStyle, CommandInteraction, CommandOptionType, Context, CreateActionRow, CreateButton,
+    CreateCommandOption, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage,
+    CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption, EditInteractionResponse,
+    ResolvedValue,
 };
 use serenity::builder::CreateCommand;
 
 use crate::Handler;
+use crate::entities::actions::ActionType;
+use crate::utils::charts::{CHART_FILENAME, generate_points_chart};
+use crate::utils::time::{TimeWindow, parse_datetime};
+use crate::utils::ui::{Highlight, LeaderboardPage, compute_scores, rank_users};
 
 pub async fn run(
     h: &Handler,
     ctx: &Context,
     command: &CommandInteraction,
 ) -> Result<(), serenity::Error> {
+    let mut as_of = None;
+    let mut user = None;
+    let mut chart = None;
+    for option in command.data.options() {
+        match (option.name, &option.value) {
+            ("as_of", ResolvedValue::String(s)) => as_of = Some(*s),
+            ("user", ResolvedValue::User(u, _)) => user = Some(u.id),
+            ("chart", ResolvedValue::Integer(i)) => chart = Some(*i as usize),
+            _ => (),
+        }
+    }
+    if let Some(top) = chart {
+        return send_chart(h, ctx, command, top).await;
+    }
+    let highlight = Highlight {
+        user_id: user.unwrap_or(command.user.id).get(),
+        is_viewer: user.is_none_or(|user| user == command.user.id),
+    };
+
+    if let Some(as_of) = as_of {
+        let data = match parse_datetime(as_of) {
+            Some(as_of) => CreateInteractionResponseMessage::new().embed(
+                crate::utils::ui::generate_snapshot_embed(
+                    &h.db_conn,
+                    as_of.timestamp(),
+                    Some(highlight),
+                )
+                .await,
+            ),
+            None => CreateInteractionResponseMessage::new().content(
+                "Invalid date, use a unix timestamp or the `YYYY-MM-DD HH:MM` format (UTC)",
+            ),
+        };
+        let builder = CreateInteractionResponse::Message(data.ephemeral(true));
+        return command.create_response(&ctx.http, builder).await;
+    }
+    if user.is_some() {
+        let data = CreateInteractionResponseMessage::new()
+            .embed(crate::utils::ui::generate_user_standing_embed(&h.db_conn, highlight).await)
+            .ephemeral(true);
+        let builder = CreateInteractionResponse::Message(data);
+        return command.create_response(&ctx.http, builder).await;
+    }
+
+    let (embed, components) = generate_leaderboard_message(
+        h,
+        "general",
+        TimeWindow::All,
+        Some(0),
+        command.user.id.get(),
+    )
+    .await;
     let data = CreateInteractionResponseMessage::new()
-        .embed(
-            crate::utils::ui::generate_leaderboard_embed(
+        .embed(embed)
+        .components(components)
+        .ephemeral(true);
+    let builder = CreateInteractionResponse::Message(data);
+    command.create_response(&ctx.http, builder).await
+}
+
+/// Replies with a chart of the points over time of the `top` users of the points leaderboard
+async fn send_chart(
+    h: &Handler,
+    ctx: &Context,
+    command: &CommandInteraction,
+    top: usize,
+) -> Result<(), serenity::Error> {
+    command.defer_ephemeral(&ctx.http).await?;
+    let score_map = match compute_scores(&h.db_conn).await {
+        Ok(score_map) => score_map,
+        Err(e) => {
+            log::error!("Error while computing scores: {e:?}");
+            return Err(serenity::Error::Other("Error while computing scores"));
+        }
+    };
+    let user_ids: Vec<_> = rank_users(&score_map, None)
+        .into_iter()
+        .take(top)
+        .map(|(user_id, _, _)| user_id)
+        .collect();
+    let builder = if user_ids.is_empty() {
+        EditInteractionResponse::new().content("Nobody has points yet !")
+    } else {
+        match generate_points_chart(&h.db_conn, ctx, "Points over time", &user_ids).await {
+            Ok(chart) => EditInteractionResponse::new()
+                .embed(
+                    CreateEmbed::new()
+                        .title("Bug Catching Contest 2025 Leaderboard")
+                        .image(format!("attachment://{CHART_FILENAME}")),
+                )
+                .new_attachment(chart),
+            Err(e) => {
+                log::error!("Error while generating leaderboard chart: {e}");
+                EditInteractionResponse::new()
+                    .content("Couldn't draw the chart, please try again later")
+            }
+        }
+    };
+    command.edit_response(&ctx.http, builder).await?;
+    Ok(())
+}
+
+/// The leaderboard of a category of the select menu restricted to `window`, with its menus and
+/// the page navigation buttons of extended categories.
+/// Shows the page of `user_id` when `page` is unset
+pub async fn generate_leaderboard_message(
+    h: &Handler,
+    category: &str,
+    window: TimeWindow,
+    page: Option<usize>,
+    user_id: u64,
+) -> (CreateEmbed, Vec<CreateActionRow>) {
+    let mut components = create_leaderboard_menus(category, window);
+    let leaderboard_page = match category {
+        "bug_confirm" | "bug_report" | "pr_fix" | "points" => {
+            let action_type = match category {
+                "bug_confirm" => Some(ActionType::ConfirmBug),
+                "bug_report" => Some(ActionType::ReportBug),
+                "pr_fix" => Some(ActionType::PRFix),
+                _ => None,
+            };
+            crate::utils::ui::generate_category_page(
                 &h.db_conn,
-                None,
-                Some(command.user.id.get()),
+                action_type,
+                window,
+                page,
+                Some(user_id),
             )
-            .await,
-        )
-        .select_menu(
+            .await
+        }
+        "teams" => {
+            crate::utils::ui::generate_team_page(&h.db_conn, window, page, Some(user_id)).await
+        }
+        "streaks" => {
+            crate::utils::ui::generate_streak_page(&h.db_conn, window, page, Some(user_id)).await
+        }
+        _ => {
+            let embed =
+                crate::utils::ui::generate_leaderboard_embed(&h.db_conn, window, Some(user_id))
+                    .await;
+            return (embed, components);
+        }
+    };
+
+    let LeaderboardPage {
+        embed,
+        page,
+        page_count,
+    } = leaderboard_page;
+    let state = format!("{category}-{}", window.id());
+    components.push(CreateActionRow::Buttons(vec![
+        CreateButton::new(format!(
+            "leaderboard-page-{state}-{}",
+            page.saturating_sub(1)
+        ))
+        .label("Previous")
+        .style(ButtonStyle::Secondary)
+        .disabled(page == 0),
+        CreateButton::new(format!("leaderboard-me-{state}"))
+            .label("Jump to me")
+            .style(ButtonStyle::Primary),
+        CreateButton::new(format!("leaderboard-page-{state}-{}", page + 1))
+            .label("Next")
+            .style(ButtonStyle::Secondary)
+            .disabled(page + 1 >= page_count),
+    ]));
+    (embed, components)
+}
+
+/// The category and time window select menus, each keeping the other's selection in its custom id
+fn create_leaderboard_menus(category: &str, window: TimeWindow) -> Vec<CreateActionRow> {
+    let category_option = |label: &str, value: &str, description: &str, emoji: char| {
+        CreateSelectMenuOption::new(label, format!("leaderboard-category-{value}"))
+            .description(description)
+            .emoji(emoji)
+            .default_selection(value == category)
+    };
+    let window_option = |label: &str, value: &str, description: &str| {
+        CreateSelectMenuOption::new(label, format!("leaderboard-window-{value}"))
+            .description(description)
+            // A custom range is never shown as selected, so that another one can be picked
+            .default_selection(value == window.id())
+    };
+    vec![
+        CreateActionRow::SelectMenu(
             CreateSelectMenu::new(
-                "leaderboard-category",
-                serenity::all::CreateSelectMenuKind::String {
+                format!("leaderboard-category-{}", window.id()),
+                CreateSelectMenuKind::String {
                     options: vec![
-                        CreateSelectMenuOption::new(
+                        category_option(
                             "Bugs Confirmed",
-                            "leaderboard-category-bug_confirm",
-                        )
-                        .description(
+                            "bug_confirm",
                             "Display an extended leaderboard for the confirmed bugs category",
-                        )
-                        .emoji('✅'),
-                        CreateSelectMenuOption::new(
+                            '✅',
+                        ),
+                        category_option(
                             "Bugs Discovered",
-                            "leaderboard-category-bug_report",
-                        )
-                        .description(
+                            "bug_report",
                             "Display an extended leaderboard for the discovered bugs category",
-                        )
-                        .emoji('🐛'),
-                        CreateSelectMenuOption::new("Bugs Solved", "leaderboard-category-pr_fix")
-                            .description(
-                                "Display an extended leaderboard for the solved bugs category",
-                            )
-                            .emoji('📍'),
-                        CreateSelectMenuOption::new("General", "leaderboard-category-general")
-                            .description("Display the default leaderboard with all categories")
-                            .emoji('⭐'),
+                            '🐛',
+                        ),
+                        category_option(
+                            "Bugs Solved",
+                            "pr_fix",
+                            "Display an extended leaderboard for the solved bugs category",
+                            '📍',
+                        ),
+                        category_option(
+                            "Points",
+                            "points",
+                            "Display an extended leaderboard of the points",
+                            '⭐',
+                        ),
+                        category_option("Teams", "teams", "Display the team leaderboard", '👥'),
+                        category_option(
+                            "Streaks",
+                            "streaks",
+                            "Display the daily and weekly contribution streaks",
+                            '🔥',
+                        ),
+                        category_option(
+                            "General",
+                            "general",
+                            "Display the default leaderboard with all categories",
+                            '🏆',
+                        ),
                     ],
                 },
             )
             .placeholder("Leaderboard categories"),
-        )
-        .ephemeral(true);
-    let builder = CreateInteractionResponse::Message(data);
-    command.create_response(&ctx.http, builder).await
+        ),
+        CreateActionRow::SelectMenu(
+            CreateSelectMenu::new(
+                format!("leaderboard-window-{category}"),
+                CreateSelectMenuKind::String {
+                    options: vec![
+                        window_option("Whole contest", "all", "Count every submission"),
+                        window_option("Today", "today", "Only count today's bugs (UTC)"),
+                        window_option("This week", "week", "Only count this week's bugs (UTC)"),
+                        window_option(
+                            "Last 7 days",
+                            "7days",
+                            "Only count the bugs of the last 7 days",
+                        ),
+                        window_option(
+                            "Custom range",
+                            "custom",
+                            "Only count the bugs between two dates",
+                        ),
+                    ],
+                },
+            )
+            .placeholder("Time window"),
+        ),
+    ]
 }
 
 pub fn register() -> CreateCommand {
-    CreateCommand::new("leaderboard").description("See the leaderboard")
+    CreateCommand::new("leaderboard")
+        .description("See the leaderboard")
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::String,
+            "as_of",
+            "See the standings at a past date (YYYY-MM-DD HH:MM in UTC, or a unix timestamp)",
+        ))
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::User,
+            "user",
+            "See the ranks around a user",
+        ))
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                "chart",
+                "Get a chart of the points over time of this many top users",
+            )
+            .min_int_value(1)
+            .max_int_value(10),
+        )
 }
@@ -0,0 +1,162 @@
+use std::hash::{BuildHasher, Hasher, RandomState};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{ActiveValue, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter};
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommandOption, EditInteractionResponse,
+    ResolvedOption, ResolvedValue,
+};
+use serenity::builder::CreateCommand;
+
+use crate::Handler;
+use crate::entities::{bot_state, linked_accounts, prelude::*};
+
+pub async fn run(
+    h: &Handler,
+    ctx: &Context,
+    command: &CommandInteraction,
+) -> Result<(), serenity::Error> {
+    let options = command.data.options();
+    let Some(ResolvedOption {
+        value: ResolvedValue::String(login),
+        ..
+    }) = options.first()
+    else {
+        return Err(serenity::Error::Other("Invalid input"));
+    };
+    command.defer_ephemeral(&ctx.http).await?;
+
+    let login = login.trim().trim_start_matches('@');
+    let profile = match octocrab::instance().users(login).profile().await {
+        Ok(p) => p,
+        Err(e) => {
+            log::debug!("Error while fetching GitHub user {login:?}: {e:?}");
+            command
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new()
+                        .content(format!("Couldn't find a GitHub user named `{login}`")),
+                )
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let user_id = command.user.id.get().to_string();
+    let linked_by_other = LinkedAccounts::find()
+        .filter(linked_accounts::Column::GithubLogin.eq(&profile.login))
+        .filter(linked_accounts::Column::UserId.ne(&user_id))
+        .one(&h.db_conn)
+        .await;
+    match linked_by_other {
+        Ok(None) => (),
+        Ok(Some(_)) => {
+            command
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new().content(format!(
+                        "`{}` is already linked to another Discord account",
+                        profile.login
+                    )),
+                )
+                .await?;
+            return Ok(());
+        }
+        Err(e) => {
+            log::error!("Error while fetching linked GitHub accounts: {e:?}");
+            return Err(serenity::Error::Other("Error while linking GitHub account"));
+        }
+    }
+
+    // The code proves the GitHub account belongs to the user, as only they can edit its bio
+    let code = match load_link_code(&h.db_conn, &user_id).await {
+        Ok(code) => code,
+        Err(e) => {
+            log::error!("Error while fetching link code of {user_id}: {e:?}");
+            return Err(serenity::Error::Other("Error while linking GitHub account"));
+        }
+    };
+    if !profile.bio.as_deref().unwrap_or_default().contains(&code) {
+        command
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content(format!(
+                    "To prove [{}]({}) is yours, add `{code}` to its bio, then link it again",
+                    profile.login, profile.html_url
+                )),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let account = linked_accounts::ActiveModel {
+        user_id: ActiveValue::Set(user_id.clone()),
+        github_login: ActiveValue::Set(profile.login.clone()),
+    };
+    if let Err(e) = LinkedAccounts::insert(account)
+        .on_conflict(
+            OnConflict::column(linked_accounts::Column::UserId)
+                .update_column(linked_accounts::Column::GithubLogin)
+                .to_owned(),
+        )
+        .exec(&h.db_conn)
+        .await
+    {
+        log::error!("Error while linking GitHub account: {e:?}");
+        return Err(serenity::Error::Other("Error while linking GitHub account"));
+    }
+    if let Err(e) = BotState::delete_by_id(link_code_key(&user_id))
+        .exec(&h.db_conn)
+        .await
+    {
+        log::error!("Error while deleting link code of {user_id}: {e:?}");
+    }
+
+    command
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new().content(format!(
+                "Linked your account to [{}]({}) ! The code can now be removed from your bio",
+                profile.login, profile.html_url
+            )),
+        )
+        .await?;
+    Ok(())
+}
+
+fn link_code_key(user_id: &str) -> String {
+    format!("link_code-{user_id}")
+}
+
+/// The code a user has to put in their GitHub bio to link it, generated on their first attempt
+async fn load_link_code(db_conn: &DatabaseConnection, user_id: &str) -> Result<String, DbErr> {
+    let key = link_code_key(user_id);
+    if let Some(state) = BotState::find_by_id(&key).one(db_conn).await? {
+        return Ok(state.value);
+    }
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write(user_id.as_bytes());
+    hasher.write_u128(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    let code = format!("caterpie-{:012x}", hasher.finish() >> 16);
+    let state = bot_state::ActiveModel {
+        key: ActiveValue::Set(key),
+        value: ActiveValue::Set(code.clone()),
+    };
+    BotState::insert(state).exec(db_conn).await?;
+    Ok(code)
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("link")
+        .description("Link your GitHub account to get suggestions when submitting")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "github", "Your GitHub username")
+                .required(true),
+        )
+}
@@ -1,5 +1,17 @@
+pub mod bounty;
+pub mod compare;
 pub mod dev;
+pub mod event;
+pub mod exclude;
+pub mod export;
+pub mod finalize;
+pub mod join;
 pub mod leaderboard;
+pub mod link;
+pub mod penalty;
 pub mod ping;
+pub mod profile;
+pub mod reconcile;
 pub mod submit;
+pub mod team;
 pub mod verify;
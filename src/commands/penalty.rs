@@ -0,0 +1,204 @@
+use chrono::Utc;
+use sea_orm::{ActiveValue, ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommandOption, CreateEmbed,
+    EditInteractionResponse, ResolvedOption, ResolvedValue, UserId,
+};
+use serenity::builder::CreateCommand;
+
+use crate::Handler;
+use crate::entities::{penalties, prelude::*};
+use crate::utils::permissions::check_senate_member;
+use crate::utils::point_roles::sync_point_roles;
+
+pub async fn run(
+    h: &Handler,
+    ctx: &Context,
+    command: &CommandInteraction,
+) -> Result<(), serenity::Error> {
+    command.defer_ephemeral(&ctx.http).await?;
+    match command.data.options().first() {
+        Some(ResolvedOption {
+            name: "add",
+            value: ResolvedValue::SubCommand(options),
+            ..
+        }) => {
+            if !check_senate_member(ctx, command).await? {
+                return Ok(());
+            }
+            add(h, ctx, command, options).await
+        }
+        Some(ResolvedOption {
+            name: "list",
+            value: ResolvedValue::SubCommand(options),
+            ..
+        }) => {
+            let user_id = match options.first() {
+                Some(ResolvedOption {
+                    value: ResolvedValue::User(user, _),
+                    ..
+                }) => user.id,
+                _ => command.user.id,
+            };
+            // Only senate members can see the history of other users
+            if user_id != command.user.id && !check_senate_member(ctx, command).await? {
+                return Ok(());
+            }
+            list(h, ctx, command, user_id).await
+        }
+        _ => Err(serenity::Error::Other("Invalid input")),
+    }
+}
+
+async fn add(
+    h: &Handler,
+    ctx: &Context,
+    command: &CommandInteraction,
+    options: &[ResolvedOption<'_>],
+) -> Result<(), serenity::Error> {
+    let mut user_id = None;
+    let mut points = None;
+    let mut reason = None;
+    for option in options {
+        match (option.name, &option.value) {
+            ("user", ResolvedValue::User(user, _)) => user_id = Some(user.id),
+            ("points", ResolvedValue::Integer(i)) => points = Some(*i),
+            ("reason", ResolvedValue::String(s)) => reason = Some(*s),
+            _ => (),
+        }
+    }
+    let (Some(user_id), Some(points), Some(reason)) = (user_id, points, reason) else {
+        return Err(serenity::Error::Other("Invalid input"));
+    };
+
+    let penalty = penalties::ActiveModel {
+        id: ActiveValue::NotSet,
+        user_id: ActiveValue::Set(user_id.get().to_string()),
+        points: ActiveValue::Set(points),
+        reason: ActiveValue::Set(reason.to_string()),
+        issued_by: ActiveValue::Set(Some(command.user.id.get().to_string())),
+        action_id: ActiveValue::Set(None),
+        created_at: ActiveValue::Set(Utc::now().timestamp()),
+    };
+    if let Err(e) = Penalties::insert(penalty).exec(&h.db_conn).await {
+        log::error!("Error while applying penalty: {e:?}");
+        return Err(serenity::Error::Other("Error while applying penalty"));
+    }
+    sync_point_roles(
+        &h.db_conn,
+        ctx,
+        command.guild_id,
+        &[user_id.get().to_string()],
+    )
+    .await;
+    command
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new().content(format!(
+                "Applied a penalty of -{points} points to <@{user_id}>: {reason}"
+            )),
+        )
+        .await?;
+    Ok(())
+}
+
+async fn list(
+    h: &Handler,
+    ctx: &Context,
+    command: &CommandInteraction,
+    user_id: UserId,
+) -> Result<(), serenity::Error> {
+    let penalties = match Penalties::find()
+        .filter(penalties::Column::UserId.eq(user_id.get().to_string()))
+        .order_by_desc(penalties::Column::CreatedAt)
+        .all(&h.db_conn)
+        .await
+    {
+        Ok(p) => p,
+        Err(e) => {
+            log::error!("Error while fetching penalties: {e:?}");
+            return Err(serenity::Error::Other("Error while fetching penalties"));
+        }
+    };
+
+    let total: i64 = penalties.iter().map(|penalty| penalty.points).sum();
+    let mut description = if penalties.is_empty() {
+        format!("<@{user_id}> has no penalty")
+    } else {
+        format!("<@{user_id}> lost **{total}** points to penalties\n")
+    };
+    for penalty in &penalties {
+        let line = format!(
+            "\n<t:{}:d> **-{}**: {}{}",
+            penalty.created_at,
+            penalty.points,
+            penalty.reason,
+            match &penalty.issued_by {
+                Some(issuer) => format!(" (by <@{issuer}>)"),
+                None => " (automatic)".to_string(),
+            }
+        );
+        if description.chars().count() + line.chars().count() > 4000 {
+            description.push_str("\n…");
+            break;
+        }
+        description.push_str(&line);
+    }
+
+    command
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new().embed(
+                CreateEmbed::new()
+                    .title("Penalty history")
+                    .description(description),
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("penalty")
+        .description("Points penalties")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "add",
+                "Deduct points from a user",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(CommandOptionType::User, "user", "The penalized user")
+                    .required(true),
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Integer,
+                    "points",
+                    "The number of points to deduct",
+                )
+                .min_int_value(1)
+                .required(true),
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "reason",
+                    "Why the penalty is applied",
+                )
+                .required(true),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "list",
+                "See the penalty history",
+            )
+            .add_sub_option(CreateCommandOption::new(
+                CommandOptionType::User,
+                "user",
+                "The user to see the penalties of (senate only), yourself by default",
+            )),
+        )
+}
@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter};
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommandOption, CreateEmbed,
+    EditInteractionResponse, ResolvedOption, ResolvedValue, UserId,
+};
+use serenity::builder::CreateCommand;
+
+use crate::Handler;
+use crate::entities::actions::{self, ActionStatus, ActionType};
+use crate::entities::{final_standings, prelude::*};
+use crate::utils::achievements::load_user_achievements;
+use crate::utils::charts::{CHART_FILENAME, generate_points_chart};
+use crate::utils::contestants::{load_display_names, user_label};
+use crate::utils::participants::{load_participants, split_points};
+use crate::utils::streaks::compute_streaks;
+use crate::utils::ui::{CATEGORIES, category_title, compute_scores, rank_users};
+
+pub async fn run(
+    h: &Handler,
+    ctx: &Context,
+    command: &CommandInteraction,
+) -> Result<(), serenity::Error> {
+    command.defer_ephemeral(&ctx.http).await?;
+
+    let user_id = match command.data.options().first() {
+        Some(ResolvedOption {
+            value: ResolvedValue::User(user, _),
+            ..
+        }) => user.id,
+        _ => command.user.id,
+    };
+    let embed = match generate_profile_embed(&h.db_conn, user_id).await {
+        Ok(embed) => embed,
+        Err(e) => {
+            log::error!("Error while generating profile of {user_id}: {e:?}");
+            return Err(serenity::Error::Other("Error while generating profile"));
+        }
+    };
+    // The profile is still useful without its chart, e.g. before the contest starts
+    let builder = match generate_points_chart(
+        &h.db_conn,
+        ctx,
+        "Points over time",
+        &[user_id.get().to_string()],
+    )
+    .await
+    {
+        Ok(chart) => EditInteractionResponse::new()
+            .embed(embed.image(format!("attachment://{CHART_FILENAME}")))
+            .new_attachment(chart),
+        Err(e) => {
+            log::error!("Error while generating points chart of {user_id}: {e}");
+            EditInteractionResponse::new().embed(embed)
+        }
+    };
+    command.edit_response(&ctx.http, builder).await?;
+    Ok(())
+}
+
+/// The submissions, score and rewards of a user
+pub async fn generate_profile_embed(
+    db_conn: &DatabaseConnection,
+    user_id: UserId,
+) -> Result<CreateEmbed, DbErr> {
+    let user_id = user_id.get().to_string();
+    let participants = load_participants(db_conn).await?;
+    // Shared submissions count for every participant, like on the leaderboard
+    let mut actions: Vec<_> = Actions::find()
+        .all(db_conn)
+        .await?
+        .into_iter()
+        .filter(|action| match participants.get(&action.id) {
+            Some(participants) => participants.iter().any(|p| p.user_id == user_id),
+            None => action.user_id == user_id,
+        })
+        .collect();
+    actions.sort_by_key(|action| action.id);
+
+    let display_names = load_display_names(db_conn).await?;
+    let github_login = LinkedAccounts::find_by_id(&user_id)
+        .one(db_conn)
+        .await?
+        .map(|account| account.github_login);
+    let mut description = format!("Profile of {}", user_label(&display_names, &user_id));
+    if let Some(login) = github_login {
+        description.push_str(&format!("\nGitHub: [{login}](https://github.com/{login})"));
+    }
+
+    let mut submissions = String::new();
+    for action_type in [
+        ActionType::ConfirmBug,
+        ActionType::ReportBug,
+        ActionType::PRFix,
+    ] {
+        let count = |status: ActionStatus| {
+            actions
+                .iter()
+                .filter(|a| a.action_type == action_type && a.action_status == status)
+                .count()
+        };
+        submissions.push_str(&format!(
+            "{action_type}: {} confirmed, {} pending, {} denied\n",
+            count(ActionStatus::Confirmed),
+            count(ActionStatus::Pending),
+            count(ActionStatus::Denied),
+        ));
+    }
+    let confirmed = actions
+        .iter()
+        .filter(|a| a.action_status == ActionStatus::Confirmed)
+        .count();
+    let denied = actions
+        .iter()
+        .filter(|a| a.action_status == ActionStatus::Denied)
+        .count();
+    let acceptance_rate = match confirmed + denied {
+        0 => "No reviewed submission".to_string(),
+        reviewed => format!("{:.0}%", confirmed as f64 * 100.0 / reviewed as f64),
+    };
+
+    let score_map = compute_scores(db_conn).await?;
+    let score = score_map.get(&user_id).copied().unwrap_or_default();
+    let rank = rank_users(&score_map, None)
+        .into_iter()
+        .find(|(id, _, _)| *id == user_id)
+        .map_or("not ranked".to_string(), |(_, rank, _)| format!("#{rank}"));
+
+    let submission_line = |action: &actions::Model| match action.github_created_at {
+        Some(created_at) => format!(
+            "[{}]({}) <t:{created_at}:d>",
+            action.action_type, action.github_link
+        ),
+        None => format!("[{}]({})", action.action_type, action.github_link),
+    };
+    // Stored points already include the event bonus, shared submissions only count the
+    // user's part of them like in their score
+    let earned = |action: &actions::Model| {
+        let points = action.points.unwrap_or(0);
+        let Some(participants) = participants.get(&action.id) else {
+            return points;
+        };
+        let shares: Vec<_> = participants
+            .iter()
+            .map(|p| (p.user_id.clone(), p.share))
+            .collect();
+        split_points(points, &action.user_id, &shares)
+            .into_iter()
+            .find(|(id, _)| *id == user_id)
+            .map_or(0, |(_, points)| points)
+    };
+    // The confirmed fix that earned the most points
+    let notable_fix = actions
+        .iter()
+        .filter(|a| {
+            a.action_type == ActionType::PRFix && a.action_status == ActionStatus::Confirmed
+        })
+        .max_by_key(|a| earned(a))
+        .map(|a| match a.linked_issue {
+            Some(issue) => format!(
+                "[Fixed #{issue}]({}) for {} points",
+                a.github_link,
+                earned(a)
+            ),
+            None => format!(
+                "[{}]({}) for {} points",
+                a.action_type,
+                a.github_link,
+                earned(a)
+            ),
+        });
+
+    let streaks = compute_streaks(db_conn)
+        .await?
+        .remove(&user_id)
+        .unwrap_or_default();
+    let badges = load_badges(db_conn, &user_id).await?;
+
+    let mut embed = CreateEmbed::new()
+        .title("Contest Profile")
+        .description(description)
+        .field("Submissions", submissions, false)
+        .field("Acceptance rate", acceptance_rate, true)
+        .field(
+            "Points",
+            format!(
+                "{}{} ({rank})",
+                score.get_total_points(),
+                score.get_points_breakdown()
+            ),
+            true,
+        );
+    if let (Some(first), Some(latest)) = (actions.first(), actions.last()) {
+        embed = embed
+            .field("First submission", submission_line(first), true)
+            .field("Latest submission", submission_line(latest), true);
+    }
+    embed = embed.field(
+        "Streaks",
+        format!(
+            "Daily: {} days (longest {})\nWeekly: {} weeks (longest {})",
+            streaks.current_daily,
+            streaks.longest_daily,
+            streaks.current_weekly,
+            streaks.longest_weekly
+        ),
+        false,
+    );
+    if let Some(notable_fix) = notable_fix {
+        embed = embed.field("Most notable fix", notable_fix, false);
+    }
+    if !badges.is_empty() {
+        embed = embed.field("Badges", badges.join("\n"), false);
+    }
+    Ok(embed)
+}
+
+/// The badges earned by a user, from the podiums of the final results and their achievements
+async fn load_badges(db_conn: &DatabaseConnection, user_id: &str) -> Result<Vec<String>, DbErr> {
+    let standings = FinalStandings::find()
+        .filter(final_standings::Column::UserId.eq(user_id))
+        .filter(final_standings::Column::Rank.lte(3))
+        .all(db_conn)
+        .await?;
+    let podiums: HashMap<_, _> = standings
+        .into_iter()
+        .map(|standing| (standing.action_type, standing.rank))
+        .collect();
+    let mut badges: Vec<String> = CATEGORIES
+        .into_iter()
+        .filter_map(|action_type| {
+            let medal = match podiums.get(&action_type)? {
+                1 => "🥇",
+                2 => "🥈",
+                _ => "🥉",
+            };
+            Some(format!("{medal} {}", category_title(action_type)))
+        })
+        .collect();
+    badges.extend(
+        load_user_achievements(db_conn, user_id)
+            .await?
+            .into_iter()
+            .map(|achievement| {
+                format!(
+                    "{} **{}**: {}",
+                    achievement.emoji, achievement.name, achievement.description
+                )
+            }),
+    );
+    Ok(badges)
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("profile")
+        .description("See the contest statistics of a user")
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::User,
+            "user",
+            "The user to see, yourself by default",
+        ))
+}
@@ -0,0 +1,36 @@
+use serenity::all::{CommandInteraction, Context, EditInteractionResponse};
+use serenity::builder::CreateCommand;
+
+use crate::CONFIG;
+use crate::Handler;
+use crate::utils::permissions::check_senate_member;
+use crate::utils::point_roles::reconcile_point_roles;
+
+pub async fn run(
+    h: &Handler,
+    ctx: &Context,
+    command: &CommandInteraction,
+) -> Result<(), serenity::Error> {
+    command.defer_ephemeral(&ctx.http).await?;
+    if !check_senate_member(ctx, command).await? {
+        return Ok(());
+    }
+
+    let content = match command.guild_id {
+        _ if CONFIG.point_roles.is_empty() => "No point role is configured".to_string(),
+        Some(guild_id) => {
+            let (added, removed) = reconcile_point_roles(&h.db_conn, ctx, guild_id).await?;
+            format!("Reconciled the point roles: added {added} and removed {removed} roles")
+        }
+        None => "You must be in a server to run this command !".to_string(),
+    };
+    command
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await?;
+    Ok(())
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("reconcile")
+        .description("Give or take back the point roles of every member according to their scores")
+}
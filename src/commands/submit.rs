@@ -1,14 +1,25 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crate::entities::actions::{ActionStatus, ActionType};
 use crate::entities::{actions, prelude::*};
-use sea_orm::{ActiveValue, ColumnTrait, EntityTrait, QueryFilter};
+use sea_orm::{ActiveValue, ColumnTrait, DbErr, EntityTrait, QueryFilter, TransactionTrait};
 
-use crate::utils::issues::IssueIds;
+use crate::utils::activity::{get_recent_activity, retain_unclaimed};
+use crate::utils::config::RuleSubject;
+use crate::utils::contestants::{check_participant, check_registration};
+use crate::utils::issues::{GithubTarget, REPO_NAME, REPO_OWNER};
+use crate::utils::participants::{
+    detect_co_authors, insert_participants, parse_mentions, split_shares,
+};
+use crate::utils::points::{Points, find_multiplier_event};
+use crate::utils::validation::{check_eligibility, find_linked_bug, validate_confirmation};
 use crate::{CONFIG, CONTEST_END_DATE, CONTEST_START_DATE, Handler};
 
 use serenity::all::*;
 
+/// How long the users credited by a submitter have to accept
+const PARTICIPATION_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
 pub async fn run(h: &Handler, ctx: &Context, command: &CommandInteraction) -> Result<(), Error> {
     if let (
         Some(ResolvedOption {
@@ -24,15 +35,28 @@ pub async fn run(h: &Handler, ctx: &Context, command: &CommandInteraction) -> Re
         &command.data.options().get(1),
     ) {
         command.defer_ephemeral(&ctx.http).await?;
+        match check_registration(&h.db_conn, &command.user.id.get().to_string()).await {
+            Ok(None) => (),
+            Ok(Some(reason)) => {
+                command
+                    .edit_response(&ctx.http, EditInteractionResponse::new().content(reason))
+                    .await?;
+                return Ok(());
+            }
+            Err(e) => {
+                log::error!("Error while checking registration: {e:?}");
+                return Err(Error::Other("Error while checking registration"));
+            }
+        }
         let action_type = match *submit_type {
             "bug_report" => ActionType::ReportBug,
             "bug_confirm" => ActionType::ConfirmBug,
             _ => ActionType::PRFix,
         };
 
-        let issue_ids_t = IssueIds::from_url(*submit_link);
-        log::debug!("{issue_ids_t:#?}");
-        if issue_ids_t.is_none() {
+        let target_t = GithubTarget::from_url(*submit_link);
+        log::debug!("{target_t:#?}");
+        if target_t.is_none() {
             command
                 .edit_response(
                     &ctx.http,
@@ -41,10 +65,44 @@ pub async fn run(h: &Handler, ctx: &Context, command: &CommandInteraction) -> Re
                 .await?;
             return Ok(());
         }
-        let issue_ids = issue_ids_t.unwrap();
+        let target = target_t.unwrap();
+
+        if !target.accepts(action_type) {
+            command
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new().content(format!(
+                        "The URL provided ({}) doesn't correspond to the specified submission type ({})\nA {} must link to {}",
+                        submit_link,
+                        action_type,
+                        action_type,
+                        match action_type {
+                            ActionType::ReportBug => "an issue (URL or `#number`)",
+                            ActionType::ConfirmBug => "a comment on an issue",
+                            ActionType::PRFix => "a pull request (URL or `#number`) or one of its comments",
+                        }
+                    )),
+                )
+                .await?;
+            return Ok(());
+        }
+        let target = target.submitted_for(action_type);
+
+        let fetched_issue = octocrab::instance()
+            .issues(REPO_OWNER, REPO_NAME)
+            .get(target.issue_number())
+            .await;
+        if let Err(e) = fetched_issue {
+            log::debug!("Error while fetching issue at {submit_link:?}: {e:?}");
+            return Err(Error::Other("Error while fetching specified url"));
+        }
+        let issue = fetched_issue.unwrap();
+        log::debug!("Fetched issue #{}: {}", issue.number, issue.title);
+
+        let github_link = target.canonical_url(issue.pull_request.is_some());
 
         if let Ok(Some(e)) = Actions::find()
-            .filter(actions::Column::GithubLink.eq(*submit_link))
+            .filter(actions::Column::GithubLink.eq(&github_link))
             .filter(actions::Column::ActionStatus.ne(ActionStatus::Denied))
             .one(&h.db_conn)
             .await
@@ -67,66 +125,102 @@ pub async fn run(h: &Handler, ctx: &Context, command: &CommandInteraction) -> Re
             return Ok(());
         }
 
-        let fetched_issue = octocrab::instance()
-            .issues("rh-hideout", "pokeemerald-expansion")
-            .get(issue_ids.issue_id)
-            .await;
-        if let Err(e) = fetched_issue {
-            log::debug!("Error while fetching issue at {submit_link:?}: {e:?}");
-            return Err(Error::Other("Error while fetching specified url"));
-        }
-        let issue = fetched_issue.unwrap();
-        log::debug!("Fetched issue #{}: {}", issue.number, issue.title);
-
         let valid_link = match action_type {
-            ActionType::ReportBug => {
-                issue.pull_request.is_none()
-                    && issue_ids.comment_id.is_none()
-                    && issue
-                        .labels
-                        .iter()
-                        .find(|label| label.name == "bug")
-                        .is_some()
-            }
-            ActionType::ConfirmBug => {
-                issue.pull_request.is_none()
-                    && issue_ids.comment_id.is_some()
-                    && issue
-                        .labels
-                        .iter()
-                        .find(|label| label.name == "bug")
-                        .is_some()
-            }
+            ActionType::ReportBug | ActionType::ConfirmBug => issue.pull_request.is_none(),
             ActionType::PRFix => issue.pull_request.is_some(),
         };
 
         if !valid_link {
-            command.edit_response(&ctx.http, EditInteractionResponse::new().content(format!("The URL provided ({}) doesn't correspond to the specified submission type ({})", submit_link, action_type.to_string()))).await?;
+            command.edit_response(&ctx.http, EditInteractionResponse::new().content(format!("The URL provided ({submit_link}) doesn't correspond to the specified submission type ({action_type})"))).await?;
             return Ok(());
         }
 
-        let action_creation_date = match action_type {
-            ActionType::PRFix | ActionType::ReportBug => issue.created_at,
+        let submitter_login = LinkedAccounts::find_by_id(command.user.id.get().to_string())
+            .one(&h.db_conn)
+            .await
+            .ok()
+            .flatten()
+            .map(|account| account.github_login);
+
+        let (action_creation_date, author_login) = match action_type {
+            ActionType::PRFix | ActionType::ReportBug => {
+                (issue.created_at, issue.user.login.clone())
+            }
             ActionType::ConfirmBug => {
                 let fetched_comment = octocrab::instance()
-                    .issues("rh-hideout", "pokeemerald-expansion")
-                    .get_comment(issue_ids.comment_id.unwrap().into())
+                    .issues(REPO_OWNER, REPO_NAME)
+                    .get_comment(target.comment_id().unwrap().into())
                     .await;
-                if let Err(_) = fetched_comment {
+                match fetched_comment {
+                    Ok(comment) => {
+                        if let Err(reason) = validate_confirmation(&issue, &comment) {
+                            command
+                                .edit_response(
+                                    &ctx.http,
+                                    EditInteractionResponse::new().content(format!(
+                                        "This comment can't be submitted as a {action_type} >.<\n{reason}"
+                                    )),
+                                )
+                                .await?;
+                            return Ok(());
+                        }
+                        (comment.created_at, comment.user.login)
+                    }
+                    Err(_) => {
+                        command
+                            .edit_response(
+                                &ctx.http,
+                                EditInteractionResponse::new().content(format!(
+                                    "Couldn't locate comment at provided URL ({submit_link})."
+                                )),
+                            )
+                            .await?;
+                        return Ok(());
+                    }
+                }
+            }
+        };
+
+        if let Err(reason) = check_eligibility(
+            action_type,
+            RuleSubject::Issue,
+            &issue,
+            &author_login,
+            submitter_login.as_deref(),
+        ) {
+            command
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new().content(format!(
+                        "This {} isn't eligible as a {action_type} >.<\n{reason}",
+                        action_type.get_github_type()
+                    )),
+                )
+                .await?;
+            return Ok(());
+        }
+
+        let linked_issue = match action_type {
+            ActionType::PRFix => match find_linked_bug(&issue, submitter_login.as_deref()).await {
+                Ok(Ok(linked)) => Some(linked),
+                Ok(Err(reason)) => {
                     command
                         .edit_response(
                             &ctx.http,
                             EditInteractionResponse::new().content(format!(
-                                "Couldn't locate comment at provided URL ({}).",
-                                submit_link
+                                "PR #{} isn't linked to an eligible bug issue >.<\n{reason}",
+                                issue.number
                             )),
                         )
                         .await?;
                     return Ok(());
-                } else {
-                    fetched_comment.unwrap().created_at
                 }
-            }
+                Err(e) => {
+                    log::debug!("Error while fetching issues linked to {submit_link:?}: {e:?}");
+                    return Err(Error::Other("Error while fetching issues linked to PR"));
+                }
+            },
+            _ => None,
         };
 
         if action_creation_date
@@ -157,20 +251,117 @@ pub async fn run(h: &Handler, ctx: &Context, command: &CommandInteraction) -> Re
             return Ok(());
         }
 
+        let event =
+            find_multiplier_event(&h.db_conn, action_type, action_creation_date.timestamp()).await;
+        let points = Points::new(action_type)
+            .with_event(event.as_ref())
+            .with_labels(
+                &linked_issue
+                    .as_ref()
+                    .unwrap_or(&issue)
+                    .labels
+                    .iter()
+                    .map(|label| label.name.clone())
+                    .collect::<Vec<_>>(),
+            );
+
+        let submitter_id = command.user.id.get().to_string();
+        let mentioned_ids = command
+            .data
+            .options()
+            .iter()
+            .find_map(|option| match option {
+                ResolvedOption {
+                    name: "participants",
+                    value: ResolvedValue::String(participants),
+                    ..
+                } => Some(parse_mentions(participants)),
+                _ => None,
+            })
+            .unwrap_or_default();
+        let co_authors = if action_type == ActionType::PRFix {
+            match detect_co_authors(&h.db_conn, issue.number).await {
+                Ok(co_authors) => co_authors,
+                Err(e) => {
+                    log::error!("Error while detecting co-authors of #{}: {e}", issue.number);
+                    vec![]
+                }
+            }
+        } else {
+            vec![]
+        };
+        let mut participant_ids = Vec::new();
+        // Mentioned users who aren't commit authors have to accept being credited
+        let mut invited_ids = Vec::new();
+        for user_id in mentioned_ids
+            .iter()
+            .chain(&co_authors)
+            .filter(|user_id| **user_id != submitter_id)
+        {
+            if participant_ids.contains(user_id) || invited_ids.contains(user_id) {
+                continue;
+            }
+            match check_participant(&h.db_conn, ctx, user_id).await {
+                Ok(None) if co_authors.contains(user_id) => participant_ids.push(user_id.clone()),
+                Ok(None) => invited_ids.push(user_id.clone()),
+                Ok(Some(reason)) if mentioned_ids.contains(user_id) => {
+                    command
+                        .edit_response(
+                            &ctx.http,
+                            EditInteractionResponse::new()
+                                .content(format!("<@{user_id}> can't be credited, they {reason}")),
+                        )
+                        .await?;
+                    return Ok(());
+                }
+                // Detected co-authors who can't take part are left out
+                Ok(Some(_)) => (),
+                Err(e) => {
+                    log::error!("Error while checking registration: {e:?}");
+                    return Err(Error::Other("Error while checking registration"));
+                }
+            }
+        }
+        let mention_list = |user_ids: &[String]| {
+            user_ids
+                .iter()
+                .map(|user_id| format!("<@{user_id}>"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let mut with_participants = String::new();
+        if !participant_ids.is_empty() {
+            with_participants.push_str(&format!(" with {}", mention_list(&participant_ids)));
+        }
+        if !invited_ids.is_empty() {
+            with_participants.push_str(&format!(
+                " (and {} once they accept)",
+                mention_list(&invited_ids)
+            ));
+        }
+
         let submitted_action = actions::ActiveModel {
             id: ActiveValue::NotSet,
-            user_id: ActiveValue::Set(command.user.id.get().to_string()),
+            user_id: ActiveValue::Set(submitter_id.clone()),
             action_type: ActiveValue::Set(action_type),
-            github_link: ActiveValue::Set(submit_link.to_string()),
+            github_link: ActiveValue::Set(github_link.clone()),
             action_status: ActiveValue::Set(actions::ActionStatus::Pending),
+            linked_issue: ActiveValue::Set(linked_issue.as_ref().map(|linked| linked.number)),
+            github_created_at: ActiveValue::Set(Some(action_creation_date.timestamp())),
+            ..Default::default()
         };
 
         let reply_builder = EditInteractionResponse::new()
-            .content(format!(
-                "Submit a {} for {} ?",
-                action_type.to_string(),
-                issue.title
-            ))
+            .content(match &linked_issue {
+                Some(linked) => format!(
+                    "Submit a {} for {} (fixes #{} - {}){} ?",
+                    action_type, issue.title, linked.number, linked.title, with_participants
+                ),
+                None => format!(
+                    "Submit a {} for {}{} ?",
+                    action_type, issue.title, with_participants
+                ),
+            })
             .button(
                 CreateButton::new("ignore-submit-confirm")
                     .style(ButtonStyle::Success)
@@ -185,7 +376,7 @@ pub async fn run(h: &Handler, ctx: &Context, command: &CommandInteraction) -> Re
         command.edit_response(&ctx.http, reply_builder).await?;
         let mut msg = command.get_response(&ctx.http).await?;
         let btn_interaction = msg
-            .await_component_interaction(&ctx)
+            .await_component_interaction(ctx)
             .timeout(Duration::from_secs(60))
             .await;
         match btn_interaction {
@@ -194,15 +385,56 @@ pub async fn run(h: &Handler, ctx: &Context, command: &CommandInteraction) -> Re
                 let args: Vec<_> = i.data.custom_id.split('-').collect();
                 let confirmed = args[2] == "confirm";
                 if confirmed {
-                    let _ = Actions::insert(submitted_action).exec(&h.db_conn).await;
+                    if !invited_ids.is_empty() {
+                        command
+                            .edit_response(
+                                &ctx.http,
+                                EditInteractionResponse::new()
+                                    .content(format!(
+                                        "Waiting for {} to accept being credited...",
+                                        mention_list(&invited_ids)
+                                    ))
+                                    .components(vec![]),
+                            )
+                            .await?;
+                        participant_ids.extend(
+                            request_participation(
+                                ctx,
+                                command,
+                                &invited_ids,
+                                &format!(
+                                    "{action_type} for [#{} - {}](<{github_link}>)",
+                                    issue.number, issue.title
+                                ),
+                            )
+                            .await?,
+                        );
+                    }
+                    let shares = split_shares(&submitter_id, &participant_ids);
+                    let with_participants = if participant_ids.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" with {}", mention_list(&participant_ids))
+                    };
+                    // The submitter would get all the credit if the participants weren't saved
+                    let saved = h
+                        .db_conn
+                        .transaction::<_, (), DbErr>(|txn| {
+                            Box::pin(async move {
+                                let res = Actions::insert(submitted_action).exec(txn).await?;
+                                insert_participants(txn, res.last_insert_id, &shares).await
+                            })
+                        })
+                        .await;
+                    if let Err(e) = saved {
+                        log::error!("Error while saving submission: {e:?}");
+                        return Err(Error::Other("Error while saving submission"));
+                    }
                     command
                         .edit_response(
                             &ctx.http,
                             EditInteractionResponse::new()
-                                .content(format!(
-                                    "Successfully submitted your {} !",
-                                    action_type.to_string()
-                                ))
+                                .content(format!("Successfully submitted your {action_type} !"))
                                 .components(vec![])
                                 .embeds(vec![]),
                         )
@@ -211,20 +443,29 @@ pub async fn run(h: &Handler, ctx: &Context, command: &CommandInteraction) -> Re
                         .feed_channel
                         .send_message(
                             &ctx.http,
-                            CreateMessage::new().embed(CreateEmbed::new().description(format!(
-                                "### <@{}> {} a bug ! +{} points\nLinked {}: [#{} - {}]({})",
-                                command.user.id.get(),
-                                match action_type {
-                                    ActionType::ConfirmBug => "confirmed",
-                                    ActionType::ReportBug => "discovered",
-                                    ActionType::PRFix => "solved",
+                            CreateMessage::new().embed(CreateEmbed::new().description(
+                                format!(
+                                    "### <@{}> {} a bug{} ! {}\nLinked {}: [#{} - {}]({})",
+                                    command.user.id.get(),
+                                    match action_type {
+                                        ActionType::ConfirmBug => "confirmed",
+                                        ActionType::ReportBug => "discovered",
+                                        ActionType::PRFix => "solved",
+                                    },
+                                    with_participants,
+                                    points,
+                                    action_type.get_github_type(),
+                                    issue.number,
+                                    issue.title,
+                                    github_link
+                                ) + &match &linked_issue {
+                                    Some(linked) => format!(
+                                        "\nFixed [#{} - {}]({})",
+                                        linked.number, linked.title, linked.html_url
+                                    ),
+                                    None => String::new(),
                                 },
-                                action_type.get_points(),
-                                action_type.get_github_type(),
-                                issue.number,
-                                issue.title,
-                                submit_link
-                            ))),
+                            )),
                         )
                         .await?;
                 } else {
@@ -255,6 +496,153 @@ pub async fn run(h: &Handler, ctx: &Context, command: &CommandInteraction) -> Re
     }
 }
 
+/// Asks the invited users whether they took part in a submission, returning those who accepted
+/// before the timeout
+async fn request_participation(
+    ctx: &Context,
+    command: &CommandInteraction,
+    invited_ids: &[String],
+    submission: &str,
+) -> Result<Vec<String>, Error> {
+    let mentions = invited_ids
+        .iter()
+        .map(|user_id| format!("<@{user_id}>"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut msg = command
+        .channel_id
+        .send_message(
+            &ctx.http,
+            CreateMessage::new()
+                .content(format!(
+                    "{mentions}, <@{}> credits you for their {submission}. Did you take part in it ?",
+                    command.user.id
+                ))
+                .button(
+                    CreateButton::new("ignore-participate-accept")
+                        .style(ButtonStyle::Success)
+                        .label("Accept"),
+                )
+                .button(
+                    CreateButton::new("ignore-participate-decline")
+                        .style(ButtonStyle::Danger)
+                        .label("Decline"),
+                ),
+        )
+        .await?;
+
+    let deadline = Instant::now() + PARTICIPATION_TIMEOUT;
+    let mut waiting = invited_ids.to_vec();
+    let mut accepted = Vec::new();
+    while !waiting.is_empty() {
+        let Some(remaining) = deadline.checked_duration_since(Instant::now()) else {
+            break;
+        };
+        let Some(i) = msg
+            .await_component_interaction(ctx)
+            .timeout(remaining)
+            .await
+        else {
+            break;
+        };
+        let user_id = i.user.id.get().to_string();
+        if !waiting.contains(&user_id) {
+            i.create_response(
+                &ctx.http,
+                CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new()
+                        .content("You aren't credited for this submission")
+                        .ephemeral(true),
+                ),
+            )
+            .await?;
+            continue;
+        }
+        i.defer(&ctx.http).await?;
+        waiting.retain(|id| *id != user_id);
+        if i.data.custom_id == "ignore-participate-accept" {
+            accepted.push(user_id);
+        }
+    }
+
+    let answered = if accepted.is_empty() {
+        "Nobody accepted".to_string()
+    } else {
+        format!(
+            "Credited {}",
+            accepted
+                .iter()
+                .map(|user_id| format!("<@{user_id}>"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    };
+    msg.edit(
+        &ctx.http,
+        EditMessage::new()
+            .content(format!(
+                "<@{}> credited {mentions} for their {submission}. {answered}",
+                command.user.id
+            ))
+            .components(vec![])
+            .allowed_mentions(CreateAllowedMentions::new()),
+    )
+    .await?;
+    Ok(accepted)
+}
+
+pub async fn autocomplete(
+    h: &Handler,
+    ctx: &Context,
+    command: &CommandInteraction,
+) -> Result<(), Error> {
+    let Some(AutocompleteOption { value: query, .. }) = command.data.autocomplete() else {
+        return Err(Error::Other("Invalid autocomplete input"));
+    };
+    let action_type = command
+        .data
+        .options()
+        .iter()
+        .find_map(|option| match option {
+            ResolvedOption {
+                name: "type",
+                value: ResolvedValue::String(submit_type),
+                ..
+            } => Some(match *submit_type {
+                "bug_report" => ActionType::ReportBug,
+                "bug_confirm" => ActionType::ConfirmBug,
+                _ => ActionType::PRFix,
+            }),
+            _ => None,
+        });
+
+    let linked_account = LinkedAccounts::find_by_id(command.user.id.get().to_string())
+        .one(&h.db_conn)
+        .await;
+    let mut items = match linked_account {
+        Ok(Some(account)) => get_recent_activity(h, &account.github_login),
+        Ok(None) => vec![],
+        Err(e) => {
+            log::error!("Error while fetching linked account: {e:?}");
+            vec![]
+        }
+    };
+    items.retain(|item| item.matches(query) && action_type.is_none_or(|t| item.is_eligible(t)));
+    retain_unclaimed(&h.db_conn, &mut items).await;
+
+    let mut response = CreateAutocompleteResponse::new();
+    for item in items.iter().take(25) {
+        let mut name = format!("#{} - {}", item.target.issue_number(), item.title);
+        if name.chars().count() > 100 {
+            name = name.chars().take(99).collect::<String>() + "…";
+        }
+        response = response.add_string_choice(name, &item.url);
+    }
+    command
+        .create_response(&ctx.http, CreateInteractionResponse::Autocomplete(response))
+        .await
+}
+
 pub fn register() -> CreateCommand {
     CreateCommand::new("submit")
         .description("Submit a bug report, a fix PR or confirm a bug")
@@ -269,10 +657,14 @@ pub fn register() -> CreateCommand {
             CreateCommandOption::new(
                 CommandOptionType::String,
                 "url",
-                "The url of the issue, comment or PR you submit for",
+                "The url (or #number) of the issue, comment or PR you submit for",
             )
-            .min_int_value(0)
-            .max_int_value(100_000)
+            .set_autocomplete(true)
             .required(true),
         )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::String,
+            "participants",
+            "Mentions of the users you worked with, who have to accept unless they authored commits of the PR",
+        ))
 }
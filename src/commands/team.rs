@@ -0,0 +1,267 @@
+use chrono::Utc;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{
+    ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
+};
+use serenity::all::{
+    AutocompleteOption, CommandInteraction, CommandOptionType, Context, CreateAutocompleteResponse,
+    CreateCommandOption, CreateEmbed, CreateInteractionResponse, CreateMessage,
+    EditInteractionResponse, ResolvedOption, ResolvedValue,
+};
+use serenity::builder::CreateCommand;
+
+use crate::entities::{prelude::*, team_members, teams};
+use crate::utils::teams::can_switch_teams;
+use crate::utils::ui::escape_markdown;
+use crate::{CONFIG, Handler};
+
+pub async fn run(
+    h: &Handler,
+    ctx: &Context,
+    command: &CommandInteraction,
+) -> Result<(), serenity::Error> {
+    command.defer_ephemeral(&ctx.http).await?;
+    let user_id = command.user.id.get().to_string();
+    let current_team = match TeamMembers::find_by_id(&user_id).one(&h.db_conn).await {
+        Ok(member) => member,
+        Err(e) => {
+            log::error!("Error while fetching team of {user_id}: {e:?}");
+            return Err(serenity::Error::Other("Error while fetching team"));
+        }
+    };
+    let now = Utc::now().timestamp();
+
+    let content = match command.data.options().first() {
+        Some(ResolvedOption {
+            name: "create",
+            value: ResolvedValue::SubCommand(options),
+            ..
+        }) => {
+            let Some(ResolvedOption {
+                value: ResolvedValue::String(name),
+                ..
+            }) = options.first()
+            else {
+                return Err(serenity::Error::Other("Invalid input"));
+            };
+            let name = name.trim();
+            if name.is_empty() {
+                "Team names can't be empty".to_string()
+            } else if current_team.is_some() && !can_switch_teams(now) {
+                switch_closed_message()
+            } else if find_team(&h.db_conn, name).await?.is_some() {
+                format!(
+                    "A team named **{}** already exists, join it with `/team join` !",
+                    escape_markdown(name)
+                )
+            } else {
+                let team = teams::ActiveModel {
+                    id: ActiveValue::NotSet,
+                    name: ActiveValue::Set(name.to_string()),
+                    created_by: ActiveValue::Set(user_id.clone()),
+                    created_at: ActiveValue::Set(now),
+                };
+                let team = match Teams::insert(team).exec_with_returning(&h.db_conn).await {
+                    Ok(team) => team,
+                    Err(e) => {
+                        log::error!("Error while creating team: {e:?}");
+                        return Err(serenity::Error::Other("Error while creating team"));
+                    }
+                };
+                set_team(&h.db_conn, &user_id, team.id, now).await?;
+                if let Some(previous) = current_team {
+                    remove_if_empty(&h.db_conn, previous.team_id).await;
+                }
+                CONFIG
+                    .feed_channel
+                    .send_message(
+                        &ctx.http,
+                        CreateMessage::new().embed(CreateEmbed::new().description(format!(
+                            "### 👥 <@{user_id}> created the team **{}** !",
+                            escape_markdown(&team.name)
+                        ))),
+                    )
+                    .await?;
+                format!(
+                    "Created and joined the team **{}** !",
+                    escape_markdown(&team.name)
+                )
+            }
+        }
+        Some(ResolvedOption {
+            name: "join",
+            value: ResolvedValue::SubCommand(options),
+            ..
+        }) => {
+            let Some(ResolvedOption {
+                value: ResolvedValue::String(name),
+                ..
+            }) = options.first()
+            else {
+                return Err(serenity::Error::Other("Invalid input"));
+            };
+            match find_team(&h.db_conn, name.trim()).await? {
+                None => format!("No team named **{}**", escape_markdown(name.trim())),
+                Some(team) if current_team.as_ref().is_some_and(|m| m.team_id == team.id) => {
+                    format!(
+                        "You are already in the team **{}**",
+                        escape_markdown(&team.name)
+                    )
+                }
+                Some(_) if current_team.is_some() && !can_switch_teams(now) => {
+                    switch_closed_message()
+                }
+                Some(team) => {
+                    set_team(&h.db_conn, &user_id, team.id, now).await?;
+                    if let Some(previous) = current_team {
+                        remove_if_empty(&h.db_conn, previous.team_id).await;
+                    }
+                    format!("Joined the team **{}** !", escape_markdown(&team.name))
+                }
+            }
+        }
+        Some(ResolvedOption { name: "leave", .. }) => match current_team {
+            None => "You are not in a team".to_string(),
+            Some(_) if !can_switch_teams(now) => switch_closed_message(),
+            Some(member) => {
+                if let Err(e) = TeamMembers::delete_by_id(&user_id).exec(&h.db_conn).await {
+                    log::error!("Error while leaving team: {e:?}");
+                    return Err(serenity::Error::Other("Error while leaving team"));
+                }
+                remove_if_empty(&h.db_conn, member.team_id).await;
+                "Left your team".to_string()
+            }
+        },
+        _ => return Err(serenity::Error::Other("Invalid input")),
+    };
+    command
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await?;
+    Ok(())
+}
+
+fn switch_closed_message() -> String {
+    format!(
+        "Teams can no longer be switched since <t:{}:f>",
+        CONFIG.team_switch_deadline.unwrap_or_default()
+    )
+}
+
+/// Finds a team by its name, ignoring case
+async fn find_team(
+    db_conn: &DatabaseConnection,
+    name: &str,
+) -> Result<Option<teams::Model>, serenity::Error> {
+    match Teams::find().all(db_conn).await {
+        Ok(teams) => Ok(teams
+            .into_iter()
+            .find(|team| team.name.eq_ignore_ascii_case(name))),
+        Err(e) => {
+            log::error!("Error while fetching teams: {e:?}");
+            Err(serenity::Error::Other("Error while fetching teams"))
+        }
+    }
+}
+
+async fn set_team(
+    db_conn: &DatabaseConnection,
+    user_id: &str,
+    team_id: u32,
+    now: i64,
+) -> Result<(), serenity::Error> {
+    let member = team_members::ActiveModel {
+        user_id: ActiveValue::Set(user_id.to_string()),
+        team_id: ActiveValue::Set(team_id),
+        joined_at: ActiveValue::Set(now),
+    };
+    if let Err(e) = TeamMembers::insert(member)
+        .on_conflict(
+            OnConflict::column(team_members::Column::UserId)
+                .update_columns([team_members::Column::TeamId, team_members::Column::JoinedAt])
+                .to_owned(),
+        )
+        .exec(db_conn)
+        .await
+    {
+        log::error!("Error while joining team {team_id}: {e:?}");
+        return Err(serenity::Error::Other("Error while joining team"));
+    }
+    Ok(())
+}
+
+/// Deletes a team once its last member left
+async fn remove_if_empty(db_conn: &DatabaseConnection, team_id: u32) {
+    let members = TeamMembers::find()
+        .filter(team_members::Column::TeamId.eq(team_id))
+        .count(db_conn)
+        .await;
+    match members {
+        Ok(0) => {
+            if let Err(e) = Teams::delete_by_id(team_id).exec(db_conn).await {
+                log::error!("Error while deleting empty team {team_id}: {e:?}");
+            }
+        }
+        Ok(_) => (),
+        Err(e) => log::error!("Error while counting members of team {team_id}: {e:?}"),
+    }
+}
+
+pub async fn autocomplete(
+    h: &Handler,
+    ctx: &Context,
+    command: &CommandInteraction,
+) -> Result<(), serenity::Error> {
+    let Some(AutocompleteOption { value: query, .. }) = command.data.autocomplete() else {
+        return Err(serenity::Error::Other("Invalid autocomplete input"));
+    };
+    let query = query.trim().to_lowercase();
+    let teams = match Teams::find().all(&h.db_conn).await {
+        Ok(teams) => teams,
+        Err(e) => {
+            log::error!("Error while fetching teams: {e:?}");
+            vec![]
+        }
+    };
+
+    let mut response = CreateAutocompleteResponse::new();
+    for team in teams
+        .iter()
+        .filter(|team| team.name.to_lowercase().contains(&query))
+        .take(25)
+    {
+        response = response.add_string_choice(&team.name, &team.name);
+    }
+    command
+        .create_response(&ctx.http, CreateInteractionResponse::Autocomplete(response))
+        .await
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("team")
+        .description("Compete together with other participants")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "create",
+                "Create a new team and join it",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(CommandOptionType::String, "name", "Name of the team")
+                    .max_length(32)
+                    .required(true),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "join", "Join a team")
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::String, "name", "Name of the team")
+                        .set_autocomplete(true)
+                        .required(true),
+                ),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "leave",
+            "Leave your team",
+        ))
+}
@@ -2,16 +2,27 @@ use std::time::Duration;
 use std::vec;
 
 use sea_orm::ActiveValue::Set;
-use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use sea_orm::{ColumnTrait, DbErr, EntityTrait, QueryFilter};
 use serenity::all::{
-    ButtonStyle, CommandInteraction, Context, CreateActionRow, CreateButton, CreateCommandOption,
-    CreateEmbed, EditInteractionResponse, EditMessage, ResolvedOption, ResolvedValue,
+    ButtonStyle, CommandInteraction, ComponentInteractionDataKind, Context, CreateActionRow,
+    CreateButton, CreateCommandOption, CreateEmbed, CreateInteractionResponseFollowup,
+    CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption, EditInteractionResponse,
+    EditMessage, ResolvedOption, ResolvedValue,
 };
 use serenity::builder::CreateCommand;
 
-use crate::EXPANSION_SENATE_ROLE;
+use crate::CONFIG;
+use crate::entities::action_participants;
 use crate::entities::actions::{self, ActionStatus};
-use crate::utils::issues::IssueIds;
+use crate::utils::achievements::check_achievements;
+use crate::utils::bounties::claim_bounties;
+use crate::utils::issues::{GithubTarget, REPO_NAME, REPO_OWNER};
+use crate::utils::participants::load_action_users;
+use crate::utils::penalties::apply_denied_penalty;
+use crate::utils::permissions::check_senate_member;
+use crate::utils::point_roles::sync_point_roles;
+use crate::utils::points::compute_confirmed_points;
+use crate::utils::streaks::announce_streak_milestones;
 use crate::{Handler, entities::prelude::*};
 
 pub async fn run(
@@ -20,31 +31,7 @@ pub async fn run(
     command: &CommandInteraction,
 ) -> Result<(), serenity::Error> {
     command.defer_ephemeral(&ctx.http).await?;
-
-    if let Some(member) = &command.member {
-        if !member
-            .roles
-            .iter()
-            .find(|r| **r == EXPANSION_SENATE_ROLE)
-            .is_some()
-        {
-            command
-                .edit_response(
-                    &ctx.http,
-                    EditInteractionResponse::new()
-                        .content("You're not an expansion senate member !"),
-                )
-                .await?;
-            return Ok(());
-        }
-    } else {
-        command
-            .edit_response(
-                &ctx.http,
-                EditInteractionResponse::new()
-                    .content("You must be in a server to run this command !"),
-            )
-            .await?;
+    if !check_senate_member(ctx, command).await? {
         return Ok(());
     }
 
@@ -67,7 +54,7 @@ pub async fn run(
             });
         command.edit_response(&ctx.http, builder).await?;
         let btn_interaction = msg
-            .await_component_interaction(&ctx)
+            .await_component_interaction(ctx)
             .timeout(Duration::from_secs(3 * 60))
             .await;
         match btn_interaction {
@@ -90,17 +77,103 @@ pub async fn run(
                     }
                 };
                 let action_id: u32 = args[2].parse().unwrap();
+                let action = match Actions::find_by_id(action_id).one(&h.db_conn).await {
+                    Ok(Some(action)) => action,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        log::error!("Error while fetching action {action_id}: {e:?}");
+                        continue;
+                    }
+                };
 
+                if !confirmed {
+                    let model = actions::ActiveModel {
+                        action_status: Set(ActionStatus::Denied),
+                        ..Default::default()
+                    };
+                    match review_pending(h, action_id, model).await {
+                        Ok(true) => (),
+                        Ok(false) => continue,
+                        Err(e) => {
+                            log::error!("Error while denying action {action_id}: {e:?}");
+                            continue;
+                        }
+                    }
+                    apply_denied_penalty(&h.db_conn, &action).await;
+                    reward_action_users(h, ctx, command, &action, false).await;
+                    continue;
+                }
+
+                let tier = if CONFIG.tiered_action_types.contains(&action.action_type)
+                    && !CONFIG.difficulty_tiers.is_empty()
+                {
+                    i.edit_response(
+                        &ctx.http,
+                        EditInteractionResponse::new().components(create_tier_select(action_id)),
+                    )
+                    .await?;
+                    let tier_interaction = msg
+                        .await_component_interaction(ctx)
+                        .timeout(Duration::from_secs(3 * 60))
+                        .await;
+                    match tier_interaction {
+                        Some(t) => {
+                            t.defer(&ctx.http).await?;
+                            match &t.data.kind {
+                                ComponentInteractionDataKind::StringSelect { values } => values
+                                    .first()
+                                    .and_then(|v| v.parse::<usize>().ok())
+                                    .and_then(|index| CONFIG.difficulty_tiers.get(index)),
+                                // Went back to the submission without confirming it
+                                _ => continue,
+                            }
+                        }
+                        None => {
+                            msg.edit(
+                                &ctx.http,
+                                EditMessage::new().content("Interaction timed out..."),
+                            )
+                            .await?;
+                            msg.components.clear();
+                            break;
+                        }
+                    }
+                } else {
+                    None
+                };
+
+                let points = match compute_confirmed_points(&h.db_conn, &action, tier).await {
+                    Ok(points) => points,
+                    Err(e) => {
+                        // Stored points are final, so the submission stays pending rather than
+                        // being confirmed without its bonuses
+                        log::error!("Error while computing points of action {action_id}: {e:?}");
+                        let followup = CreateInteractionResponseFollowup::new()
+                            .content("Couldn't compute the points, the submission is still pending")
+                            .ephemeral(true);
+                        i.create_followup(&ctx.http, followup).await?;
+                        continue;
+                    }
+                };
                 let model = actions::ActiveModel {
-                    id: Set(action_id),
-                    action_status: Set(if confirmed {
-                        ActionStatus::Confirmed
-                    } else {
-                        ActionStatus::Denied
-                    }),
+                    action_status: Set(ActionStatus::Confirmed),
+                    points: Set(Some(points.total())),
+                    event_id: Set(points.event_id),
+                    event_points: Set(Some(points.event_points())),
+                    difficulty: Set(tier.map(|tier| tier.name.clone())),
                     ..Default::default()
                 };
-                let _ = Actions::update(model).exec(&h.db_conn).await;
+                match review_pending(h, action_id, model).await {
+                    Ok(true) => (),
+                    Ok(false) => continue,
+                    Err(e) => {
+                        log::error!("Error while confirming action {action_id}: {e:?}");
+                        continue;
+                    }
+                }
+                claim_bounties(&h.db_conn, ctx, &action).await;
+                check_achievements(&h.db_conn, ctx, command.guild_id, &action).await;
+                reward_action_users(h, ctx, command, &action, true).await;
             }
             None => {
                 msg.edit(
@@ -126,26 +199,95 @@ pub fn register() -> CreateCommand {
         ))
 }
 
+/// Applies a review to a submission if it is still pending, returning whether it was.
+/// Keeps two senators reviewing the same submission from rewarding it twice
+async fn review_pending(
+    h: &Handler,
+    action_id: u32,
+    model: actions::ActiveModel,
+) -> Result<bool, DbErr> {
+    let res = Actions::update_many()
+        .set(model)
+        .filter(actions::Column::Id.eq(action_id))
+        .filter(actions::Column::ActionStatus.eq(ActionStatus::Pending))
+        .exec(&h.db_conn)
+        .await?;
+    Ok(res.rows_affected > 0)
+}
+
+/// Updates the point roles of the users credited for a reviewed submission,
+/// and announces the streaks a confirmed one extended
+async fn reward_action_users(
+    h: &Handler,
+    ctx: &Context,
+    command: &CommandInteraction,
+    action: &actions::Model,
+    confirmed: bool,
+) {
+    let user_ids = match load_action_users(&h.db_conn, action).await {
+        Ok(user_ids) => user_ids,
+        Err(e) => {
+            log::error!(
+                "Error while fetching participants of action {}: {e:?}",
+                action.id
+            );
+            return;
+        }
+    };
+    sync_point_roles(&h.db_conn, ctx, command.guild_id, &user_ids).await;
+    if confirmed {
+        announce_streak_milestones(&h.db_conn, ctx, &user_ids).await;
+    }
+}
+
+fn create_tier_select(action_id: u32) -> Vec<CreateActionRow> {
+    vec![
+        CreateActionRow::SelectMenu(
+            CreateSelectMenu::new(
+                format!("ignore-tier-{action_id}"),
+                CreateSelectMenuKind::String {
+                    options: CONFIG
+                        .difficulty_tiers
+                        .iter()
+                        .enumerate()
+                        .map(|(index, tier)| {
+                            CreateSelectMenuOption::new(&tier.name, index.to_string())
+                                .description(format!("{} points", tier.points))
+                                .emoji(tier.emoji)
+                        })
+                        .collect(),
+                },
+            )
+            .placeholder("Difficulty of the submission"),
+        ),
+        CreateActionRow::Buttons(vec![
+            CreateButton::new(format!("ignore-back-{action_id}"))
+                .label("Back")
+                .style(ButtonStyle::Secondary),
+        ]),
+    ]
+}
+
 pub async fn create_verification_message(
     h: &Handler,
     user: Option<u64>,
 ) -> (CreateEmbed, Option<CreateActionRow>, u32) {
-    let action_pending = match user {
+    let actions_pending = match user {
         None => {
             Actions::find()
                 .filter(actions::Column::ActionStatus.eq(actions::ActionStatus::Pending))
-                .one(&h.db_conn)
+                .all(&h.db_conn)
                 .await
         }
         Some(u) => {
             Actions::find()
                 .filter(actions::Column::ActionStatus.eq(actions::ActionStatus::Pending))
                 .filter(actions::Column::UserId.eq(u))
-                .one(&h.db_conn)
+                .all(&h.db_conn)
                 .await
         }
     };
-    if let Err(e) = action_pending {
+    if let Err(e) = actions_pending {
         log::error!("Error while fetching action submissions: {e:?}");
         return (
             CreateEmbed::new().description("I encountered an error while fetching submissions >.<"),
@@ -153,20 +295,54 @@ pub async fn create_verification_message(
             0,
         );
     }
-    if let Some(action) = action_pending.unwrap() {
-        let issue_ids = IssueIds::from_url(&action.github_link).unwrap();
-        let issue = octocrab::instance()
-            .issues("rh-hideout", "pokeemerald-expansion")
-            .get(issue_ids.issue_id)
+    // Submissions whose issue can't be fetched are skipped until it can be
+    for action in actions_pending.unwrap() {
+        let Some(target) = GithubTarget::from_url(&action.github_link) else {
+            log::error!(
+                "Skipped action {} with an invalid link: {}",
+                action.id,
+                action.github_link
+            );
+            continue;
+        };
+        let issue = match octocrab::instance()
+            .issues(REPO_OWNER, REPO_NAME)
+            .get(target.issue_number())
+            .await
+        {
+            Ok(issue) => issue,
+            Err(e) => {
+                log::error!(
+                    "Skipped action {} as its issue couldn't be fetched: {e:?}",
+                    action.id
+                );
+                continue;
+            }
+        };
+        let participants = ActionParticipants::find()
+            .filter(action_participants::Column::ActionId.eq(action.id))
+            .all(&h.db_conn)
             .await
-            .unwrap();
+            .unwrap_or_default();
         return (
             CreateEmbed::new().description(format!(
-                "Submission by <@{}>: **{}** for **(#{}) {}**",
+                "Submission by <@{}>: **{}** for **(#{}) {}**{}",
                 action.user_id,
-                action.action_type.to_string(),
+                action.action_type,
                 issue.number,
-                issue.title
+                issue.title,
+                if participants.is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        "\nShared between {}",
+                        participants
+                            .iter()
+                            .map(|p| format!("<@{}> ({:.0}%)", p.user_id, p.share * 100.0))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                }
             )),
             Some(CreateActionRow::Buttons(vec![
                 CreateButton::new_link(action.github_link)
@@ -186,14 +362,13 @@ pub async fn create_verification_message(
             ])),
             action.id,
         );
-    } else {
-        return (
-            CreateEmbed::new().description(match user {
-                None => "No pending submission left".to_string(),
-                Some(u) => format!("No pending submission left for <@{u}>"),
-            }),
-            None,
-            0,
-        );
     }
+    (
+        CreateEmbed::new().description(match user {
+            None => "No pending submission left".to_string(),
+            Some(u) => format!("No pending submission left for <@{u}>"),
+        }),
+        None,
+        0,
+    )
 }
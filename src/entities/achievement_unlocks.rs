@@ -0,0 +1,17 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "AchievementUnlocks")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = true)]
+    pub id: u32,
+    pub user_id: String,
+    /// Id of the unlocked achievement in the config
+    pub achievement_id: String,
+    pub unlocked_at: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
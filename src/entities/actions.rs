@@ -1,4 +1,5 @@
 use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
 #[sea_orm(table_name = "Actions")]
@@ -11,9 +12,23 @@ pub struct Model {
     pub action_type: ActionType,
     pub github_link: String,
     pub user_id: String,
+    /// The bug issue closed or referenced by a PRFix submission
+    pub linked_issue: Option<u64>,
+    /// Points awarded when the submission was confirmed
+    pub points: Option<i64>,
+    /// Name of the difficulty tier picked by the reviewer
+    pub difficulty: Option<String>,
+    /// When the submitted item was created on GitHub, as a unix timestamp
+    pub github_created_at: Option<i64>,
+    /// The multiplier event applied when the submission was confirmed
+    pub event_id: Option<u32>,
+    /// Points gained from the multiplier event
+    pub event_points: Option<i64>,
 }
 
-#[derive(EnumIter, DeriveActiveEnum, PartialEq, Eq, Clone, Copy, Debug)]
+#[derive(
+    EnumIter, DeriveActiveEnum, PartialEq, Eq, Hash, Clone, Copy, Debug, Serialize, Deserialize,
+)]
 #[sea_orm(rs_type = "String", db_type = "String(StringLen::N(1))")]
 pub enum ActionType {
     #[sea_orm(string_value = "R")]
@@ -33,7 +48,7 @@ impl ActionType {
         }
     }
 
-    pub fn get_points(&self) -> u8 {
+    pub fn get_points(&self) -> i64 {
         match self {
             Self::ConfirmBug => 1,
             Self::ReportBug => 2,
@@ -42,13 +57,13 @@ impl ActionType {
     }
 }
 
-impl ToString for ActionType {
-    fn to_string(&self) -> String {
-        match self {
-            Self::ReportBug => "Bug Report".to_string(),
-            Self::ConfirmBug => "Bug Confirmation".to_string(),
-            Self::PRFix => "Bugfix PR".to_string(),
-        }
+impl std::fmt::Display for ActionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::ReportBug => "Bug Report",
+            Self::ConfirmBug => "Bug Confirmation",
+            Self::PRFix => "Bugfix PR",
+        })
     }
 }
 
@@ -0,0 +1,28 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "Bounties")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = true)]
+    pub id: u32,
+    pub issue_number: u64,
+    pub points: i64,
+    pub created_by: String,
+    pub created_at: i64,
+    pub expires_at: Option<i64>,
+    pub claimed_by: Option<String>,
+    pub claimed_action: Option<u32>,
+    pub claimed_at: Option<i64>,
+}
+
+impl Model {
+    /// Whether the bounty can still be collected at `timestamp`
+    pub fn is_open(&self, timestamp: i64) -> bool {
+        self.claimed_by.is_none() && self.expires_at.is_none_or(|expiry| timestamp <= expiry)
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
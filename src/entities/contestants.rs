@@ -0,0 +1,19 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "Contestants")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub user_id: String,
+    /// Name displayed on the leaderboards next to the user's mention
+    pub display_name: Option<String>,
+    /// When the user accepted the contest rules, unset for users excluded before joining
+    pub accepted_at: Option<i64>,
+    /// Excluded contestants can't submit and are hidden from the leaderboards
+    pub excluded: bool,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,22 @@
+use sea_orm::entity::prelude::*;
+
+use super::actions::ActionType;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "FinalStandings")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = true)]
+    pub id: u32,
+    /// Leaderboard category, the points category when unset
+    pub action_type: Option<ActionType>,
+    /// Tied users share the same rank
+    pub rank: u32,
+    pub user_id: String,
+    pub value: i64,
+    pub finalized_at: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,23 @@
+use sea_orm::entity::prelude::*;
+
+use super::actions::ActionType;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "LeaderboardSnapshots")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = true)]
+    pub id: u32,
+    /// All the rows of a snapshot share the time it was taken at
+    pub taken_at: i64,
+    /// Leaderboard category, the points category when unset
+    pub action_type: Option<ActionType>,
+    /// Tied users share the same rank
+    pub rank: u32,
+    pub user_id: String,
+    pub value: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
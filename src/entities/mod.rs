@@ -2,4 +2,16 @@
 
 pub mod prelude;
 
+pub mod achievement_unlocks;
+pub mod action_participants;
 pub mod actions;
+pub mod bot_state;
+pub mod bounties;
+pub mod contestants;
+pub mod final_standings;
+pub mod leaderboard_snapshots;
+pub mod linked_accounts;
+pub mod multiplier_events;
+pub mod penalties;
+pub mod team_members;
+pub mod teams;
@@ -0,0 +1,35 @@
+use sea_orm::entity::prelude::*;
+
+use super::actions::ActionType;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "MultiplierEvents")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = true)]
+    pub id: u32,
+    pub name: String,
+    pub starts_at: i64,
+    pub ends_at: i64,
+    pub factor: f64,
+    /// Only submissions of this type are multiplied, all of them when unset
+    pub action_type: Option<ActionType>,
+    pub created_by: String,
+    pub created_at: i64,
+    pub cancelled_by: Option<String>,
+    pub cancelled_at: Option<i64>,
+}
+
+impl Model {
+    /// Whether the event multiplies a submission of `action_type` created on GitHub at `timestamp`
+    pub fn applies_to(&self, action_type: ActionType, timestamp: i64) -> bool {
+        self.cancelled_at.is_none()
+            && self.starts_at <= timestamp
+            && timestamp < self.ends_at
+            && self.action_type.is_none_or(|t| t == action_type)
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,22 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "Penalties")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = true)]
+    pub id: u32,
+    pub user_id: String,
+    /// Points deducted from the user's total
+    pub points: i64,
+    pub reason: String,
+    /// The senate member who applied the penalty, unset for automatic penalties
+    pub issued_by: Option<String>,
+    /// The denied submission an automatic penalty was applied for
+    pub action_id: Option<u32>,
+    pub created_at: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,3 +1,15 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.12
 
+pub use super::achievement_unlocks::Entity as AchievementUnlocks;
+pub use super::action_participants::Entity as ActionParticipants;
 pub use super::actions::Entity as Actions;
+pub use super::bot_state::Entity as BotState;
+pub use super::bounties::Entity as Bounties;
+pub use super::contestants::Entity as Contestants;
+pub use super::final_standings::Entity as FinalStandings;
+pub use super::leaderboard_snapshots::Entity as LeaderboardSnapshots;
+pub use super::linked_accounts::Entity as LinkedAccounts;
+pub use super::multiplier_events::Entity as MultiplierEvents;
+pub use super::penalties::Entity as Penalties;
+pub use super::team_members::Entity as TeamMembers;
+pub use super::teams::Entity as Teams;
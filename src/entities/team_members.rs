@@ -0,0 +1,16 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "TeamMembers")]
+pub struct Model {
+    /// A user can only be in one team at a time
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub user_id: String,
+    pub team_id: u32,
+    pub joined_at: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,42 @@
+use serenity::all::{
+    ComponentInteraction, Context, CreateInteractionResponse, CreateInteractionResponseMessage,
+};
+
+use crate::Handler;
+use crate::commands::leaderboard::generate_leaderboard_message;
+use crate::utils::time::TimeWindow;
+
+pub async fn run(
+    h: &Handler,
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+) -> Result<(), serenity::Error> {
+    // leaderboard-page-{category}-{window}-{page} or leaderboard-me-{category}-{window}
+    let args: Vec<_> = interaction.data.custom_id.split('-').collect();
+    let (Some(category), Some(window)) = (
+        args.get(2),
+        args.get(3).and_then(|id| TimeWindow::from_id(id)),
+    ) else {
+        return Err(serenity::Error::Other("Invalid leaderboard button"));
+    };
+    let page = match args[1] {
+        "page" => match args.get(4).and_then(|page| page.parse().ok()) {
+            Some(page) => Some(page),
+            None => return Err(serenity::Error::Other("Invalid leaderboard page")),
+        },
+        "me" => None,
+        _ => {
+            return Err(serenity::Error::Other(
+                "Leaderboard button subcommand not implemented",
+            ));
+        }
+    };
+
+    let (embed, components) =
+        generate_leaderboard_message(h, category, window, page, interaction.user.id.get()).await;
+    let data = CreateInteractionResponseMessage::new()
+        .embed(embed)
+        .components(components);
+    let builder = CreateInteractionResponse::UpdateMessage(data);
+    interaction.create_response(&ctx.http, builder).await
+}
@@ -1 +1,3 @@
+pub mod buttons;
+pub mod modals;
 pub mod selectmenus;
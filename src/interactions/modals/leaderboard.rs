@@ -0,0 +1,61 @@
+use serenity::all::{
+    ActionRowComponent, Context, CreateInteractionResponse, CreateInteractionResponseMessage,
+    ModalInteraction,
+};
+
+use crate::Handler;
+use crate::commands::leaderboard::generate_leaderboard_message;
+use crate::utils::time::{TimeWindow, parse_datetime};
+
+pub async fn run(
+    h: &Handler,
+    ctx: &Context,
+    interaction: &ModalInteraction,
+) -> Result<(), serenity::Error> {
+    let args: Vec<_> = interaction.data.custom_id.split('-').collect();
+    if args[1] != "range" {
+        return Err(serenity::Error::Other(
+            "Leaderboard modal subcommand not implemented",
+        ));
+    }
+    let category = args.get(2).copied().unwrap_or("general");
+
+    let input = |custom_id: &str| {
+        interaction
+            .data
+            .components
+            .iter()
+            .flat_map(|row| &row.components)
+            .find_map(|component| match component {
+                ActionRowComponent::InputText(input) if input.custom_id == custom_id => {
+                    input.value.as_deref().and_then(parse_datetime)
+                }
+                _ => None,
+            })
+    };
+    let (Some(start), Some(end)) = (input("start"), input("end")) else {
+        let data = CreateInteractionResponseMessage::new()
+            .content("Invalid date, use a unix timestamp or the `YYYY-MM-DD HH:MM` format (UTC)")
+            .ephemeral(true);
+        return interaction
+            .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+            .await;
+    };
+    if end <= start {
+        let data = CreateInteractionResponseMessage::new()
+            .content("The end of the window must be after its start !")
+            .ephemeral(true);
+        return interaction
+            .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+            .await;
+    }
+
+    let window = TimeWindow::Custom(start.timestamp(), end.timestamp());
+    let (embed, components) =
+        generate_leaderboard_message(h, category, window, Some(0), interaction.user.id.get()).await;
+    let data = CreateInteractionResponseMessage::new()
+        .embed(embed)
+        .components(components);
+    let builder = CreateInteractionResponse::UpdateMessage(data);
+    interaction.create_response(&ctx.http, builder).await
+}
@@ -1,35 +1,65 @@
 use serenity::all::{
-    ComponentInteraction, Context, CreateInteractionResponse, CreateInteractionResponseMessage,
+    ComponentInteraction, Context, CreateActionRow, CreateInputText, CreateInteractionResponse,
+    CreateInteractionResponseMessage, CreateModal, InputTextStyle,
 };
 
-use crate::{Handler, entities::actions::ActionType};
+use crate::Handler;
+use crate::commands::leaderboard::generate_leaderboard_message;
+use crate::utils::time::TimeWindow;
 
 pub async fn run(
     h: &Handler,
     ctx: &Context,
     interaction: &ComponentInteraction,
-    values: &Vec<String>,
+    values: &[String],
 ) -> Result<(), serenity::Error> {
-    match interaction.data.custom_id.split('-').collect::<Vec<_>>()[1] {
-        "category" => {
-            let data = CreateInteractionResponseMessage::new().embed(
-                crate::utils::ui::generate_leaderboard_embed(
-                    &h.db_conn,
-                    match values.first().unwrap().split('-').collect::<Vec<_>>()[2] {
-                        "bug_confirm" => Some(ActionType::ConfirmBug),
-                        "bug_report" => Some(ActionType::ReportBug),
-                        "pr_fix" => Some(ActionType::PRFix),
-                        _ => None,
-                    },
-                    Some(interaction.user.id.get()),
-                )
-                .await,
-            );
-            let builder = CreateInteractionResponse::UpdateMessage(data);
-            interaction.create_response(&ctx.http, builder).await
+    let args: Vec<_> = interaction.data.custom_id.split('-').collect();
+    let Some(value) = values.first().and_then(|v| v.split('-').nth(2)) else {
+        return Err(serenity::Error::Other("Invalid leaderboard selection"));
+    };
+    let (category, window) = match args[1] {
+        // The custom id holds the selected window, missing on messages sent before windows existed
+        "category" => (
+            value,
+            args.get(2)
+                .and_then(|id| TimeWindow::from_id(id))
+                .unwrap_or(TimeWindow::All),
+        ),
+        "window" if value == "custom" => {
+            let modal = CreateModal::new(
+                format!("leaderboard-range-{}", args.get(2).unwrap_or(&"general")),
+                "Custom time window",
+            )
+            .components(vec![
+                CreateActionRow::InputText(
+                    CreateInputText::new(InputTextStyle::Short, "Start (UTC)", "start")
+                        .placeholder("YYYY-MM-DD HH:MM"),
+                ),
+                CreateActionRow::InputText(
+                    CreateInputText::new(InputTextStyle::Short, "End, excluded (UTC)", "end")
+                        .placeholder("YYYY-MM-DD HH:MM"),
+                ),
+            ]);
+            return interaction
+                .create_response(&ctx.http, CreateInteractionResponse::Modal(modal))
+                .await;
         }
-        _ => Err(serenity::Error::Other(
-            "Leaderboard select menu subcommand not implemented",
-        )),
-    }
+        "window" => (
+            args.get(2).copied().unwrap_or("general"),
+            TimeWindow::from_id(value).unwrap_or(TimeWindow::All),
+        ),
+        _ => {
+            return Err(serenity::Error::Other(
+                "Leaderboard select menu subcommand not implemented",
+            ));
+        }
+    };
+
+    let (embed, components) =
+        generate_leaderboard_message(h, category, window, Some(0), interaction.user.id.get()).await;
+    let data = CreateInteractionResponseMessage::new()
+        .embed(embed)
+        .components(components);
+    let builder = CreateInteractionResponse::UpdateMessage(data);
+    interaction.create_response(&ctx.http, builder).await
 }
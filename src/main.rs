@@ -4,37 +4,133 @@ mod interactions;
 mod utils;
 
 use chrono::{DateTime, Utc};
-use sea_orm::{ConnectionTrait, Database, DatabaseConnection, Schema};
-use serenity::all::{ChannelId, ComponentInteractionDataKind, GuildId, MessageId, Ready, RoleId};
+use sea_orm::sea_query::{Index, Table};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ConnectionTrait, Database, DatabaseConnection, EntityTrait,
+    Schema,
+};
+use serenity::all::{ChannelId, ComponentInteractionDataKind, MessageId, Ready, RoleId};
 use serenity::all::{
     Command, CreateInteractionResponse, CreateInteractionResponseMessage, Interaction,
 };
 use serenity::async_trait;
 use serenity::prelude::*;
 
+use std::collections::HashMap;
 use std::env;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, LazyLock};
+use std::sync::{Arc, LazyLock, Mutex};
 use std::time::Duration;
 
 use log::{debug, error, info};
 
-use crate::utils::config::Config;
+use crate::entities::actions::ActionType;
+use crate::utils::activity::ActivityCache;
+use crate::utils::config::{
+    Config, CreditSplit, DeniedPenalty, DifficultyTier, EligibilityRule, LabelBonus, RuleSubject,
+};
+use crate::utils::issues::canonical_link;
+use crate::utils::phase::{ContestPhase, announce_phase_transition};
+use crate::utils::results::finalize_results_if_due;
+use crate::utils::snapshots::take_snapshot_if_due;
 use crate::utils::ui::update_permanent_leaderboard;
 
 pub struct Handler {
     db_conn: DatabaseConnection,
     is_loop_running: AtomicBool,
+    activity_cache: ActivityCache,
 }
 
 static CONFIG: LazyLock<Config> = LazyLock::new(|| Config {
     contest_start_timestamp: 1759771800,
     contest_end_timestamp: 1764613800,
+    submission_grace_end_timestamp: 1764873000,
+    review_end_timestamp: 1765477800,
     feed_channel: ChannelId::new(1387772097471840266),
     permanent_leaderboard: (
         ChannelId::new(1386765701590814842),
         MessageId::from(1394934058261413960),
     ),
+    contest_rules: "\
+        - Only bugs of pokeemerald-expansion count\n\
+        - Each issue, comment or PR can only be submitted once\n\
+        - The expansion senate reviews submissions, its decision is final\n\
+        - Spamming low effort submissions gets you penalized or excluded"
+        .to_string(),
+    require_registration: false,
+    contestant_role: None,
+    confirmation_keywords: vec![],
+    label_bonuses: vec![
+        LabelBonus {
+            label: "severity: critical".to_string(),
+            multiplier: 2.0,
+            bonus: 0,
+        },
+        LabelBonus {
+            label: "crash".to_string(),
+            multiplier: 1.0,
+            bonus: 1,
+        },
+    ],
+    difficulty_tiers: vec![
+        DifficultyTier {
+            name: "Trivial".to_string(),
+            emoji: '🌱',
+            points: 2,
+        },
+        DifficultyTier {
+            name: "Normal".to_string(),
+            emoji: '🔧',
+            points: 5,
+        },
+        DifficultyTier {
+            name: "Hard".to_string(),
+            emoji: '🔥',
+            points: 10,
+        },
+        DifficultyTier {
+            name: "Epic".to_string(),
+            emoji: '🐉',
+            points: 20,
+        },
+    ],
+    tiered_action_types: vec![ActionType::PRFix],
+    credit_split: CreditSplit::Equal,
+    team_switch_deadline: Some(1760981400),
+    denied_penalty: Some(DeniedPenalty {
+        threshold: 3,
+        points: 1,
+    }),
+    eligibility_rules: vec![
+        EligibilityRule {
+            name: "Bug reports must be labelled bug".to_string(),
+            action_types: vec![ActionType::ReportBug, ActionType::ConfirmBug],
+            subject: RuleSubject::Issue,
+            required_labels: vec!["bug".to_string()],
+            forbidden_labels: vec![],
+            any_of_labels: vec![],
+            states: vec![],
+            excluded_authors: vec![],
+            author_must_be_submitter: false,
+        },
+        EligibilityRule {
+            name: "Bugfix PRs must fix an issue labelled bug".to_string(),
+            action_types: vec![ActionType::PRFix],
+            subject: RuleSubject::LinkedIssue,
+            required_labels: vec!["bug".to_string()],
+            forbidden_labels: vec![],
+            any_of_labels: vec![],
+            states: vec![],
+            excluded_authors: vec![],
+            author_must_be_submitter: false,
+        },
+    ],
+    snapshot_interval: 60 * 60,
+    rank_movement_period: 24 * 60 * 60,
+    winner_roles: vec![],
+    point_roles: vec![],
+    daily_streak_milestones: vec![3, 7, 14],
+    weekly_streak_milestones: vec![4],
 });
 
 static CONTEST_START_DATE: LazyLock<DateTime<Utc>> =
@@ -51,12 +147,39 @@ impl EventHandler for Handler {
             Interaction::Command(command) => {
                 debug!("Received command interaction: {command:#?}");
 
+                let phase = ContestPhase::current();
+                if !phase.allows_command(&command.data.name) {
+                    let data = CreateInteractionResponseMessage::new()
+                        .content(format!(
+                            "`/{}` isn't available during the {phase} phase of the contest",
+                            command.data.name
+                        ))
+                        .ephemeral(true);
+                    let builder = CreateInteractionResponse::Message(data);
+                    if let Err(e) = command.create_response(&ctx.http, builder).await {
+                        error!("Error while refusing command {}: {e:?}", command.data.name);
+                    }
+                    return;
+                }
+
                 let res = match command.data.name.as_str() {
-                    "ping" => commands::ping::run(&self, &ctx, &command).await,
-                    "submit" => commands::submit::run(&self, &ctx, &command).await,
-                    "leaderboard" => commands::leaderboard::run(&self, &ctx, &command).await,
-                    "verify" => commands::verify::run(&self, &ctx, &command).await,
-                    "dev" => commands::dev::run(&self, &ctx, &command).await,
+                    "ping" => commands::ping::run(self, &ctx, &command).await,
+                    "submit" => commands::submit::run(self, &ctx, &command).await,
+                    "bounty" => commands::bounty::run(self, &ctx, &command).await,
+                    "leaderboard" => commands::leaderboard::run(self, &ctx, &command).await,
+                    "link" => commands::link::run(self, &ctx, &command).await,
+                    "penalty" => commands::penalty::run(self, &ctx, &command).await,
+                    "verify" => commands::verify::run(self, &ctx, &command).await,
+                    "dev" => commands::dev::run(self, &ctx, &command).await,
+                    "event" => commands::event::run(self, &ctx, &command).await,
+                    "join" => commands::join::run(self, &ctx, &command).await,
+                    "exclude" => commands::exclude::run(self, &ctx, &command).await,
+                    "finalize" => commands::finalize::run(self, &ctx, &command).await,
+                    "team" => commands::team::run(self, &ctx, &command).await,
+                    "compare" => commands::compare::run(self, &ctx, &command).await,
+                    "profile" => commands::profile::run(self, &ctx, &command).await,
+                    "reconcile" => commands::reconcile::run(self, &ctx, &command).await,
+                    "export" => commands::export::run(self, &ctx, &command).await,
                     _ => Err(SerenityError::Other("command not implemented")),
                 };
 
@@ -67,6 +190,38 @@ impl EventHandler for Handler {
                     let _ = command.create_response(&ctx.http, builder).await;
                 }
             }
+            Interaction::Autocomplete(command) => {
+                let res = match command.data.name.as_str() {
+                    "submit" => commands::submit::autocomplete(self, &ctx, &command).await,
+                    "team" => commands::team::autocomplete(self, &ctx, &command).await,
+                    _ => Err(SerenityError::Other("autocomplete not implemented")),
+                };
+
+                if let Err(e) = res {
+                    error!(
+                        "Error while running autocomplete for {}: {e:?}",
+                        command.data.name
+                    );
+                }
+            }
+            Interaction::Modal(interaction) => {
+                let res = match interaction.data.custom_id.split('-').next() {
+                    Some("leaderboard") => {
+                        interactions::modals::leaderboard::run(self, &ctx, &interaction).await
+                    }
+                    _ => Err(SerenityError::Other("modal not implemented")),
+                };
+
+                if let Err(e) = res {
+                    error!(
+                        "Error while running modal interaction {}: {e:?}",
+                        interaction.data.custom_id
+                    );
+                    let data = CreateInteractionResponseMessage::new().content("Encountered an error while running modal interaction, please report to the developers").ephemeral(true);
+                    let builder = CreateInteractionResponse::Message(data);
+                    let _ = interaction.create_response(&ctx.http, builder).await;
+                }
+            }
             Interaction::Component(interaction) => {
                 let args: Vec<&str> = interaction.data.custom_id.split('-').collect();
                 if args[0] == "ignore" {
@@ -76,15 +231,21 @@ impl EventHandler for Handler {
                     ComponentInteractionDataKind::StringSelect { ref values } => match args[0] {
                         "leaderboard" => {
                             interactions::selectmenus::leaderboard::run(
-                                &self,
+                                self,
                                 &ctx,
                                 &interaction,
-                                &values,
+                                values,
                             )
                             .await
                         }
                         _ => Err(SerenityError::Other("interaction not implemented")),
                     },
+                    ComponentInteractionDataKind::Button => match args[0] {
+                        "leaderboard" => {
+                            interactions::buttons::leaderboard::run(self, &ctx, &interaction).await
+                        }
+                        _ => Err(SerenityError::Other("interaction not implemented")),
+                    },
                     _ => Err(SerenityError::Other(
                         "component interaction type not implemented",
                     )),
@@ -118,9 +279,21 @@ impl EventHandler for Handler {
             vec![
                 commands::ping::register(),
                 commands::submit::register(),
+                commands::bounty::register(),
                 commands::leaderboard::register(),
+                commands::link::register(),
+                commands::penalty::register(),
                 commands::verify::register(),
                 commands::dev::register(),
+                commands::event::register(),
+                commands::join::register(),
+                commands::exclude::register(),
+                commands::finalize::register(),
+                commands::team::register(),
+                commands::compare::register(),
+                commands::profile::register(),
+                commands::reconcile::register(),
+                commands::export::register(),
             ],
         )
         .await;
@@ -134,6 +307,9 @@ impl EventHandler for Handler {
             let db_conn1 = Arc::clone(&db_conn);
             tokio::spawn(async move {
                 loop {
+                    announce_phase_transition(&db_conn1, &ctx1).await;
+                    finalize_results_if_due(&db_conn1, &ctx1).await;
+                    take_snapshot_if_due(&db_conn1).await;
                     update_permanent_leaderboard(&db_conn1, &ctx1).await;
                     tokio::time::sleep(Duration::from_secs(120)).await;
                 }
@@ -166,7 +342,10 @@ async fn main() {
     );
 
     let token = env::var("DISCORD_TOKEN").expect("Expected a token in the environment");
-    let intents = GatewayIntents::GUILD_MESSAGES | GatewayIntents::MESSAGE_CONTENT;
+    // Listing the members to reconcile their point roles needs the privileged members intent
+    let intents = GatewayIntents::GUILD_MESSAGES
+        | GatewayIntents::MESSAGE_CONTENT
+        | GatewayIntents::GUILD_MEMBERS;
 
     let db_url: String = format!("sqlite:{working_dir}/caterpie.db?mode=rwc");
     let db_t = Database::connect(db_url).await;
@@ -178,24 +357,131 @@ async fn main() {
 
     let builder = db.get_database_backend();
     let schema = Schema::new(builder);
+    for statement in [
+        schema
+            .create_table_from_entity(crate::entities::prelude::AchievementUnlocks)
+            .if_not_exists()
+            .to_owned(),
+        schema
+            .create_table_from_entity(crate::entities::prelude::Actions)
+            .if_not_exists()
+            .to_owned(),
+        schema
+            .create_table_from_entity(crate::entities::prelude::ActionParticipants)
+            .if_not_exists()
+            .to_owned(),
+        schema
+            .create_table_from_entity(crate::entities::prelude::FinalStandings)
+            .if_not_exists()
+            .to_owned(),
+        schema
+            .create_table_from_entity(crate::entities::prelude::LeaderboardSnapshots)
+            .if_not_exists()
+            .to_owned(),
+        schema
+            .create_table_from_entity(crate::entities::prelude::LinkedAccounts)
+            .if_not_exists()
+            .to_owned(),
+        schema
+            .create_table_from_entity(crate::entities::prelude::Bounties)
+            .if_not_exists()
+            .to_owned(),
+        schema
+            .create_table_from_entity(crate::entities::prelude::BotState)
+            .if_not_exists()
+            .to_owned(),
+        schema
+            .create_table_from_entity(crate::entities::prelude::Contestants)
+            .if_not_exists()
+            .to_owned(),
+        schema
+            .create_table_from_entity(crate::entities::prelude::MultiplierEvents)
+            .if_not_exists()
+            .to_owned(),
+        schema
+            .create_table_from_entity(crate::entities::prelude::Penalties)
+            .if_not_exists()
+            .to_owned(),
+        schema
+            .create_table_from_entity(crate::entities::prelude::Teams)
+            .if_not_exists()
+            .to_owned(),
+        schema
+            .create_table_from_entity(crate::entities::prelude::TeamMembers)
+            .if_not_exists()
+            .to_owned(),
+    ] {
+        if let Err(err) = db.execute(builder.build(&statement)).await {
+            error!("Error while creating database tables: {err:?}");
+            return;
+        }
+    }
+    // Keeps an achievement from being unlocked twice by the same user, after removing the
+    // duplicates unlocked before the index existed
     if let Err(err) = db
-        .execute(
-            builder.build(
-                schema
-                    .create_table_from_entity(crate::entities::prelude::Actions)
-                    .if_not_exists(),
-            ),
+        .execute_unprepared(
+            "DELETE FROM AchievementUnlocks WHERE id NOT IN \
+            (SELECT MIN(id) FROM AchievementUnlocks GROUP BY user_id, achievement_id)",
         )
         .await
     {
-        error!("Error while creating database tables: {err:?}");
-        return;
+        error!("Error while removing duplicate achievement unlocks: {err:?}");
+    }
+    let unlocks_index = Index::create()
+        .name("idx-achievement_unlocks-user_id-achievement_id")
+        .table(crate::entities::prelude::AchievementUnlocks)
+        .col(crate::entities::achievement_unlocks::Column::UserId)
+        .col(crate::entities::achievement_unlocks::Column::AchievementId)
+        .unique()
+        .if_not_exists()
+        .to_owned();
+    if let Err(err) = db.execute(builder.build(&unlocks_index)).await {
+        error!("Error while creating achievement unlocks index: {err:?}");
+    }
+    // Columns added after the Actions table was first created, this fails once they already exist
+    for column in [
+        crate::entities::actions::Column::LinkedIssue,
+        crate::entities::actions::Column::Points,
+        crate::entities::actions::Column::Difficulty,
+        crate::entities::actions::Column::GithubCreatedAt,
+        crate::entities::actions::Column::EventId,
+        crate::entities::actions::Column::EventPoints,
+    ] {
+        let statement = Table::alter()
+            .table(crate::entities::prelude::Actions)
+            .add_column(schema.get_column_def::<crate::entities::prelude::Actions>(column))
+            .to_owned();
+        if let Err(err) = db.execute(builder.build(&statement)).await {
+            debug!("Skipped adding database column: {err:?}");
+        }
+    }
+    // Links were stored as submitted before being canonicalized, older submissions would
+    // otherwise escape the duplicate check of /submit
+    match crate::entities::prelude::Actions::find().all(&db).await {
+        Ok(actions) => {
+            for action in actions {
+                let Some(link) = canonical_link(&action.github_link, action.action_type) else {
+                    continue;
+                };
+                if link == action.github_link {
+                    continue;
+                }
+                let id = action.id;
+                let mut model: crate::entities::actions::ActiveModel = action.into();
+                model.github_link = ActiveValue::Set(link);
+                if let Err(err) = model.update(&db).await {
+                    error!("Error while canonicalizing link of action {id}: {err:?}");
+                }
+            }
+        }
+        Err(err) => error!("Error while fetching actions to canonicalize their links: {err:?}"),
     }
 
     let mut client = Client::builder(&token, intents)
         .event_handler(Handler {
             db_conn: db,
             is_loop_running: AtomicBool::new(false),
+            activity_cache: Arc::new(Mutex::new(HashMap::new())),
         })
         .await
         .expect("Err creating client");
@@ -0,0 +1,473 @@
+use std::collections::HashMap;
+use std::{env, fs, io};
+
+use chrono::Utc;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{ActiveValue, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter};
+use serenity::all::{Context, CreateEmbed, CreateMessage, GuildId, UserId};
+
+use crate::CONFIG;
+use crate::entities::actions::{self, ActionStatus, ActionType};
+use crate::entities::{achievement_unlocks, action_participants, bounties, prelude::*};
+use crate::utils::config::{Achievement, AchievementRule};
+use crate::utils::issues::GithubTarget;
+use crate::utils::participants::load_participants;
+
+/// The achievements users can unlock, read from `config/achievements.json` on every use so
+/// that they can be changed while the bot is running
+pub fn load_achievements() -> Vec<Achievement> {
+    let working_dir = env::var("WORKDIR").unwrap_or(".".to_string());
+    let path = format!("{working_dir}/config/achievements.json");
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return vec![],
+        Err(e) => {
+            log::error!("Error while reading achievements from {path}: {e:?}");
+            return vec![];
+        }
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        log::error!("Error while parsing achievements from {path}: {e:?}");
+        vec![]
+    })
+}
+
+/// Unlocks the achievements earned by the participants of a newly confirmed submission,
+/// announcing them in the feed and granting their roles
+pub async fn check_achievements(
+    db_conn: &DatabaseConnection,
+    ctx: &Context,
+    guild_id: Option<GuildId>,
+    action: &actions::Model,
+) {
+    let achievements = load_achievements();
+    if achievements.is_empty() {
+        return;
+    }
+    let unlocked = match evaluate_achievements(db_conn, action, achievements).await {
+        Ok(unlocked) => unlocked,
+        Err(e) => {
+            log::error!(
+                "Error while checking achievements of action {}: {e:?}",
+                action.id
+            );
+            return;
+        }
+    };
+
+    let now = Utc::now().timestamp();
+    for (user_id, achievement) in unlocked {
+        let unlock = achievement_unlocks::ActiveModel {
+            id: ActiveValue::NotSet,
+            user_id: ActiveValue::Set(user_id.clone()),
+            achievement_id: ActiveValue::Set(achievement.id.clone()),
+            unlocked_at: ActiveValue::Set(now),
+        };
+        // Confirmations evaluated at the same time can't unlock the same achievement twice
+        match AchievementUnlocks::insert(unlock)
+            .on_conflict(
+                OnConflict::columns([
+                    achievement_unlocks::Column::UserId,
+                    achievement_unlocks::Column::AchievementId,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec(db_conn)
+            .await
+        {
+            Ok(_) => (),
+            Err(DbErr::RecordNotInserted) => continue,
+            Err(e) => {
+                log::error!(
+                    "Error while unlocking achievement {} for {user_id}: {e:?}",
+                    achievement.id
+                );
+                continue;
+            }
+        }
+        if let Err(e) = CONFIG
+            .feed_channel
+            .send_message(
+                &ctx.http,
+                CreateMessage::new().embed(CreateEmbed::new().description(format!(
+                    "### 🏅 <@{user_id}> unlocked {} **{}** !\n{}",
+                    achievement.emoji, achievement.name, achievement.description
+                ))),
+            )
+            .await
+        {
+            log::error!(
+                "Error while announcing achievement {} of {user_id}: {e:?}",
+                achievement.id
+            );
+        }
+        if let (Some(role_id), Some(guild_id), Ok(user)) =
+            (achievement.role, guild_id, user_id.parse::<UserId>())
+            && let Err(e) = ctx
+                .http
+                .add_member_role(guild_id, user, role_id, Some("Achievement unlocked"))
+                .await
+        {
+            log::error!(
+                "Error while granting role of achievement {} to {user_id}: {e:?}",
+                achievement.id
+            );
+        }
+    }
+}
+
+/// The achievements the participants of `action` newly fulfil the rules of
+async fn evaluate_achievements(
+    db_conn: &DatabaseConnection,
+    action: &actions::Model,
+    achievements: Vec<Achievement>,
+) -> Result<Vec<(String, Achievement)>, DbErr> {
+    let rows = AchievementRows {
+        participants: load_participants(db_conn).await?,
+        confirmed: Actions::find()
+            .filter(actions::Column::ActionStatus.eq(ActionStatus::Confirmed))
+            .all(db_conn)
+            .await?,
+        unlocks: AchievementUnlocks::find().all(db_conn).await?,
+        collected_bounties: Bounties::find()
+            .filter(bounties::Column::ClaimedBy.is_not_null())
+            .all(db_conn)
+            .await?,
+    };
+    Ok(rows.newly_unlocked(action, achievements))
+}
+
+/// Everything the rules of achievements are checked against
+struct AchievementRows {
+    participants: HashMap<u32, Vec<action_participants::Model>>,
+    confirmed: Vec<actions::Model>,
+    unlocks: Vec<achievement_unlocks::Model>,
+    collected_bounties: Vec<bounties::Model>,
+}
+
+impl AchievementRows {
+    /// Whether `user_id` is credited for the submission `action_id` submitted by `submitter`
+    fn takes_part(&self, action_id: u32, submitter: &str, user_id: &str) -> bool {
+        match self.participants.get(&action_id) {
+            Some(participants) => participants.iter().any(|p| p.user_id == user_id),
+            None => submitter == user_id,
+        }
+    }
+
+    /// The achievements the participants of `action` newly fulfil the rules of
+    fn newly_unlocked(
+        &self,
+        action: &actions::Model,
+        achievements: Vec<Achievement>,
+    ) -> Vec<(String, Achievement)> {
+        let users: Vec<String> = match self.participants.get(&action.id) {
+            Some(participants) => participants.iter().map(|p| p.user_id.clone()).collect(),
+            None => vec![action.user_id.clone()],
+        };
+        // When the reports of bugs were created on GitHub, by issue number
+        let report_times: HashMap<u64, i64> = self
+            .confirmed
+            .iter()
+            .filter(|a| a.action_type == ActionType::ReportBug)
+            .filter_map(|a| {
+                Some((
+                    GithubTarget::from_url(&a.github_link)?.issue_number(),
+                    a.github_created_at?,
+                ))
+            })
+            .collect();
+        // The confirmed submission of each type created first on GitHub, shared by all its
+        // participants
+        let first_of_type: HashMap<ActionType, u32> = [
+            ActionType::ReportBug,
+            ActionType::ConfirmBug,
+            ActionType::PRFix,
+        ]
+        .into_iter()
+        .filter_map(|action_type| {
+            let first = self
+                .confirmed
+                .iter()
+                .filter(|a| a.action_type == action_type)
+                .min_by_key(|a| (a.github_created_at.unwrap_or(i64::MAX), a.id))?;
+            Some((action_type, first.id))
+        })
+        .collect();
+
+        let mut unlocked: Vec<(String, Achievement)> = vec![];
+        for user_id in users {
+            // Shared submissions count for every participant, like on the leaderboard
+            let user_actions: Vec<_> = self
+                .confirmed
+                .iter()
+                .filter(|a| self.takes_part(a.id, &a.user_id, &user_id))
+                .collect();
+            for achievement in &achievements {
+                // Firsts of the contest can only be unlocked through a single submission
+                let exclusive = matches!(achievement.rule, AchievementRule::FirstOfContest { .. });
+                let already_unlocked = self.unlocks.iter().any(|unlock| {
+                    unlock.achievement_id == achievement.id
+                        && (exclusive || unlock.user_id == user_id)
+                });
+                if already_unlocked {
+                    continue;
+                }
+                let fulfilled = match achievement.rule {
+                    AchievementRule::Confirmed { action_type, count } => {
+                        user_actions
+                            .iter()
+                            .filter(|a| action_type.is_none_or(|t| a.action_type == t))
+                            .count() as u64
+                            >= count
+                    }
+                    AchievementRule::QuickFix { within } => user_actions.iter().any(|a| {
+                        let (ActionType::PRFix, Some(issue), Some(fixed_at)) =
+                            (a.action_type, a.linked_issue, a.github_created_at)
+                        else {
+                            return false;
+                        };
+                        let delay = report_times
+                            .get(&issue)
+                            .map(|reported_at| fixed_at - reported_at);
+                        delay.is_some_and(|delay| (0..=within).contains(&delay))
+                    }),
+                    AchievementRule::FirstOfContest { action_type } => first_of_type
+                        .get(&action_type)
+                        .is_some_and(|first| user_actions.iter().any(|a| a.id == *first)),
+                    AchievementRule::Bounties { count } => {
+                        self.collected_bounties
+                            .iter()
+                            .filter(|b| match (b.claimed_action, &b.claimed_by) {
+                                (Some(action_id), Some(claimed_by)) => {
+                                    self.takes_part(action_id, claimed_by, &user_id)
+                                }
+                                (None, claimed_by) => claimed_by.as_ref() == Some(&user_id),
+                                (Some(_), None) => false,
+                            })
+                            .count() as u64
+                            >= count
+                    }
+                };
+                if fulfilled {
+                    unlocked.push((user_id.clone(), achievement.clone()));
+                }
+            }
+        }
+        unlocked
+    }
+}
+
+/// The achievements a user unlocked, in the order of the achievements file
+pub async fn load_user_achievements(
+    db_conn: &DatabaseConnection,
+    user_id: &str,
+) -> Result<Vec<Achievement>, DbErr> {
+    let unlocks = AchievementUnlocks::find()
+        .filter(achievement_unlocks::Column::UserId.eq(user_id))
+        .all(db_conn)
+        .await?;
+    Ok(load_achievements()
+        .into_iter()
+        .filter(|achievement| unlocks.iter().any(|u| u.achievement_id == achievement.id))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_bundled_achievements() {
+        let content = include_str!("../../config/achievements.json");
+        let achievements: Vec<Achievement> = serde_json::from_str(content).unwrap();
+        assert!(!achievements.is_empty());
+        assert!(achievements.iter().any(|a| matches!(
+            a.rule,
+            AchievementRule::FirstOfContest {
+                action_type: ActionType::PRFix
+            }
+        )));
+    }
+
+    const BASE: &str = "https://github.com/rh-hideout/pokeemerald-expansion";
+
+    fn action(id: u32, action_type: ActionType, user_id: &str, created_at: i64) -> actions::Model {
+        actions::Model {
+            id,
+            action_status: ActionStatus::Confirmed,
+            action_type,
+            github_link: format!("{BASE}/issues/{id}"),
+            user_id: user_id.to_string(),
+            linked_issue: None,
+            points: Some(action_type.get_points()),
+            difficulty: None,
+            github_created_at: Some(created_at),
+            event_id: None,
+            event_points: None,
+        }
+    }
+
+    fn achievement(id: &str, rule: AchievementRule) -> Achievement {
+        Achievement {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            emoji: '🏅',
+            rule,
+            role: None,
+        }
+    }
+
+    fn participant(action_id: u32, user_id: &str) -> action_participants::Model {
+        action_participants::Model {
+            id: 0,
+            action_id,
+            user_id: user_id.to_string(),
+            share: 0.5,
+        }
+    }
+
+    fn rows(confirmed: Vec<actions::Model>) -> AchievementRows {
+        AchievementRows {
+            participants: HashMap::new(),
+            confirmed,
+            unlocks: vec![],
+            collected_bounties: vec![],
+        }
+    }
+
+    fn unlocked_users(
+        rows: &AchievementRows,
+        action: &actions::Model,
+        achievement: &Achievement,
+    ) -> Vec<String> {
+        rows.newly_unlocked(action, vec![achievement.clone()])
+            .into_iter()
+            .map(|(user_id, _)| user_id)
+            .collect()
+    }
+
+    #[test]
+    fn counts_confirmed_submissions() {
+        let confirmed = vec![
+            action(1, ActionType::ConfirmBug, "a", 10),
+            action(2, ActionType::ConfirmBug, "a", 20),
+            action(3, ActionType::ReportBug, "a", 30),
+        ];
+        let rows = rows(confirmed.clone());
+        let two_confirmations = achievement(
+            "two",
+            AchievementRule::Confirmed {
+                action_type: Some(ActionType::ConfirmBug),
+                count: 2,
+            },
+        );
+        let three_confirmations = achievement(
+            "three",
+            AchievementRule::Confirmed {
+                action_type: Some(ActionType::ConfirmBug),
+                count: 3,
+            },
+        );
+        let three_submissions = achievement(
+            "any",
+            AchievementRule::Confirmed {
+                action_type: None,
+                count: 3,
+            },
+        );
+        assert_eq!(
+            unlocked_users(&rows, &confirmed[1], &two_confirmations),
+            ["a"]
+        );
+        assert!(unlocked_users(&rows, &confirmed[1], &three_confirmations).is_empty());
+        assert_eq!(
+            unlocked_users(&rows, &confirmed[2], &three_submissions),
+            ["a"]
+        );
+    }
+
+    #[test]
+    fn skips_achievements_already_unlocked() {
+        let confirmed = vec![action(1, ActionType::ReportBug, "a", 10)];
+        let mut rows = rows(confirmed.clone());
+        rows.unlocks.push(achievement_unlocks::Model {
+            id: 1,
+            user_id: "a".to_string(),
+            achievement_id: "first_bug".to_string(),
+            unlocked_at: 0,
+        });
+        let first_bug = achievement(
+            "first_bug",
+            AchievementRule::Confirmed {
+                action_type: None,
+                count: 1,
+            },
+        );
+        assert!(unlocked_users(&rows, &confirmed[0], &first_bug).is_empty());
+    }
+
+    #[test]
+    fn awards_quick_fixes() {
+        let report = action(12, ActionType::ReportBug, "a", 1000);
+        let mut quick = action(20, ActionType::PRFix, "b", 1000 + 3600);
+        quick.linked_issue = Some(12);
+        let mut slow = action(21, ActionType::PRFix, "c", 1000 + 2 * 86400);
+        slow.linked_issue = Some(12);
+        let rows = rows(vec![report, quick.clone(), slow.clone()]);
+        let quick_fix = achievement("quick_fix", AchievementRule::QuickFix { within: 86400 });
+        assert_eq!(unlocked_users(&rows, &quick, &quick_fix), ["b"]);
+        assert!(unlocked_users(&rows, &slow, &quick_fix).is_empty());
+    }
+
+    #[test]
+    fn awards_first_of_contest_by_creation_date() {
+        // Confirmed first, but created after the shared fix
+        let later = action(1, ActionType::PRFix, "a", 200);
+        let earlier = action(2, ActionType::PRFix, "b", 100);
+        let mut rows = rows(vec![later.clone(), earlier.clone()]);
+        rows.participants
+            .insert(2, vec![participant(2, "b"), participant(2, "c")]);
+        let first_fix = achievement(
+            "first_fix",
+            AchievementRule::FirstOfContest {
+                action_type: ActionType::PRFix,
+            },
+        );
+        assert!(unlocked_users(&rows, &later, &first_fix).is_empty());
+        // Every participant of the first submission gets it
+        assert_eq!(unlocked_users(&rows, &earlier, &first_fix), ["b", "c"]);
+
+        rows.unlocks.push(achievement_unlocks::Model {
+            id: 1,
+            user_id: "b".to_string(),
+            achievement_id: "first_fix".to_string(),
+            unlocked_at: 0,
+        });
+        assert!(unlocked_users(&rows, &earlier, &first_fix).is_empty());
+    }
+
+    #[test]
+    fn counts_shared_bounties() {
+        let fix = action(5, ActionType::PRFix, "a", 100);
+        let mut rows = rows(vec![fix.clone()]);
+        rows.participants
+            .insert(5, vec![participant(5, "a"), participant(5, "b")]);
+        rows.collected_bounties.push(bounties::Model {
+            id: 1,
+            issue_number: 12,
+            points: 10,
+            created_by: "senate".to_string(),
+            created_at: 0,
+            expires_at: None,
+            claimed_by: Some("a".to_string()),
+            claimed_action: Some(5),
+            claimed_at: Some(150),
+        });
+        let one_bounty = achievement("bounty", AchievementRule::Bounties { count: 1 });
+        let two_bounties = achievement("bounties", AchievementRule::Bounties { count: 2 });
+        assert_eq!(unlocked_users(&rows, &fix, &one_bounty), ["a", "b"]);
+        assert!(unlocked_users(&rows, &fix, &two_bounties).is_empty());
+    }
+}
@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+
+use crate::entities::actions::{self, ActionStatus, ActionType};
+use crate::entities::prelude::*;
+use crate::utils::config::RuleSubject;
+use crate::utils::issues::{GithubTarget, REPO_NAME, REPO_OWNER};
+use crate::utils::validation::rules_for;
+use crate::{CONTEST_END_DATE, CONTEST_START_DATE, Handler};
+
+/// How long the GitHub activity of a user is reused between autocomplete requests
+const ACTIVITY_CACHE_DURATION: Duration = Duration::from_secs(60);
+/// Number of issues and PRs the comments of a user are looked for in, each costing a request
+const COMMENTED_ISSUES_LIMIT: u8 = 10;
+/// How long listing the comments of one issue or PR may take before it is skipped
+const COMMENTS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The recent activity of each GitHub login, with when it was fetched
+pub type ActivityCache = Arc<Mutex<HashMap<String, (Instant, Vec<ActivityItem>)>>>;
+
+/// An issue, PR or comment created by a user in the contest repository
+#[derive(Clone, Debug)]
+pub struct ActivityItem {
+    pub target: GithubTarget,
+    pub url: String,
+    pub title: String,
+    pub is_pull_request: bool,
+    /// Labels of the issue or PR, unknown for comments
+    pub labels: Option<Vec<String>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ActivityItem {
+    /// Whether this item could be submitted for the given submission type
+    pub fn is_eligible(&self, action_type: ActionType) -> bool {
+        self.target.accepts(action_type)
+            && match action_type {
+                ActionType::ReportBug | ActionType::ConfirmBug => !self.is_pull_request,
+                // Comments of a PR are submitted as the PR, which is suggested on its own
+                ActionType::PRFix => matches!(self.target, GithubTarget::PullRequest { .. }),
+            }
+            && self.labels.as_ref().is_none_or(|labels| {
+                rules_for(action_type, RuleSubject::Issue)
+                    .all(|rule| rule.check_labels(labels).is_ok())
+            })
+    }
+
+    /// Whether the text typed by the user matches this item's number or title
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.trim().trim_start_matches('#').to_lowercase();
+        query.is_empty()
+            || self.target.issue_number().to_string().starts_with(&query)
+            || self.title.to_lowercase().contains(&query)
+    }
+}
+
+/// Fetches the issues, PRs and comments recently created by `login` during the contest
+async fn fetch_recent_activity(login: &str) -> octocrab::Result<Vec<ActivityItem>> {
+    let mut items = Vec::new();
+
+    let issues = octocrab::instance()
+        .search()
+        .issues_and_pull_requests(&format!(
+            "repo:{REPO_OWNER}/{REPO_NAME} author:{login} created:{}..{}",
+            CONTEST_START_DATE.format("%Y-%m-%dT%H:%M:%SZ"),
+            CONTEST_END_DATE.format("%Y-%m-%dT%H:%M:%SZ"),
+        ))
+        .sort("created")
+        .order("desc")
+        .per_page(50)
+        .send()
+        .await?;
+    for issue in issues.items {
+        let is_pull_request = issue.pull_request.is_some();
+        let target = if is_pull_request {
+            GithubTarget::PullRequest {
+                number: issue.number,
+            }
+        } else {
+            GithubTarget::Issue {
+                number: issue.number,
+            }
+        };
+        items.push(ActivityItem {
+            target,
+            url: target.canonical_url(is_pull_request),
+            title: issue.title,
+            is_pull_request,
+            labels: Some(issue.labels.into_iter().map(|label| label.name).collect()),
+            created_at: issue.created_at,
+        });
+    }
+
+    // Comments can't be searched, so they are listed on the issues and PRs the user
+    // commented on since the start of the contest
+    let commented = octocrab::instance()
+        .search()
+        .issues_and_pull_requests(&format!(
+            "repo:{REPO_OWNER}/{REPO_NAME} commenter:{login} updated:>={}",
+            CONTEST_START_DATE.format("%Y-%m-%dT%H:%M:%SZ"),
+        ))
+        .sort("updated")
+        .order("desc")
+        .per_page(COMMENTED_ISSUES_LIMIT)
+        .send()
+        .await?;
+    let comment_lists = join_all(commented.items.iter().map(|issue| {
+        let number = issue.number;
+        tokio::time::timeout(COMMENTS_TIMEOUT, async move {
+            octocrab::instance()
+                .issues(REPO_OWNER, REPO_NAME)
+                .list_comments(number)
+                .since(*CONTEST_START_DATE)
+                .per_page(100)
+                .send()
+                .await
+        })
+    }))
+    .await;
+    for (issue, comments) in commented.items.iter().zip(comment_lists) {
+        let comments = match comments {
+            Ok(Ok(comments)) => comments,
+            Ok(Err(e)) => {
+                log::error!("Error while listing comments of #{}: {e:?}", issue.number);
+                continue;
+            }
+            Err(_) => {
+                log::error!("Timed out while listing comments of #{}", issue.number);
+                continue;
+            }
+        };
+        for comment in comments.items {
+            if !comment.user.login.eq_ignore_ascii_case(login) {
+                continue;
+            }
+            let Some(target) = GithubTarget::from_url(comment.html_url.as_str()) else {
+                continue;
+            };
+            let is_pull_request = matches!(target, GithubTarget::PullRequestComment { .. });
+            let snippet: String = comment
+                .body
+                .unwrap_or_default()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            items.push(ActivityItem {
+                target,
+                url: target.canonical_url(is_pull_request),
+                title: format!("Comment: {snippet}"),
+                is_pull_request,
+                labels: None,
+                created_at: comment.created_at,
+            });
+        }
+    }
+
+    items.retain(|item| {
+        item.created_at >= *CONTEST_START_DATE && item.created_at <= *CONTEST_END_DATE
+    });
+    items.sort_by_key(|item| std::cmp::Reverse(item.created_at));
+    Ok(items)
+}
+
+/// Returns the cached activity of `login` right away, as autocomplete has to answer within
+/// 3 seconds. A missing or stale entry is refreshed in the background for the next requests
+pub fn get_recent_activity(h: &Handler, login: &str) -> Vec<ActivityItem> {
+    let key = login.to_lowercase();
+    let (refresh, items) = {
+        let mut cache = h.activity_cache.lock().unwrap();
+        match cache.get_mut(&key) {
+            Some((fetched_at, items)) => {
+                let stale = fetched_at.elapsed() >= ACTIVITY_CACHE_DURATION;
+                // Keeps the following keystrokes from starting the same refresh
+                if stale {
+                    *fetched_at = Instant::now();
+                }
+                (stale, items.clone())
+            }
+            None => {
+                cache.insert(key.clone(), (Instant::now(), vec![]));
+                (true, vec![])
+            }
+        }
+    };
+    if !refresh {
+        return items;
+    }
+
+    let cache = h.activity_cache.clone();
+    let login = login.to_string();
+    tokio::spawn(async move {
+        match fetch_recent_activity(&login).await {
+            Ok(fetched) => {
+                cache.lock().unwrap().insert(key, (Instant::now(), fetched));
+            }
+            Err(e) => log::error!("Error while fetching GitHub activity of {login}: {e:?}"),
+        }
+    });
+    items
+}
+
+/// Removes the items that were already submitted and not denied
+pub async fn retain_unclaimed(db_conn: &DatabaseConnection, items: &mut Vec<ActivityItem>) {
+    let claimed = Actions::find()
+        .filter(actions::Column::GithubLink.is_in(items.iter().map(|item| item.url.clone())))
+        .filter(actions::Column::ActionStatus.ne(ActionStatus::Denied))
+        .all(db_conn)
+        .await;
+    match claimed {
+        Ok(claimed) => {
+            items.retain(|item| !claimed.iter().any(|action| action.github_link == item.url))
+        }
+        Err(e) => log::error!("Error while fetching claimed submissions: {e:?}"),
+    }
+}
@@ -0,0 +1,142 @@
+use chrono::Utc;
+use octocrab::models::Event;
+use octocrab::models::pulls::PullRequest;
+use sea_orm::ActiveValue::Set;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use serenity::all::{Context, CreateEmbed, CreateMessage};
+
+use crate::CONFIG;
+use crate::entities::actions::{self, ActionType};
+use crate::entities::{bounties, prelude::*};
+use crate::utils::issues::{GithubTarget, REPO_NAME, REPO_OWNER, closing_references};
+use crate::utils::participants::load_action_users;
+
+/// Whether a bugfix PR closes an issue, through a closing keyword in its description
+/// or by having closed it when it was merged
+async fn closes_issue(pr: &PullRequest, issue_number: u64) -> octocrab::Result<bool> {
+    if closing_references(pr.body.as_deref().unwrap_or_default()).contains(&issue_number) {
+        return Ok(true);
+    }
+    let Some(merge_commit) = &pr.merge_commit_sha else {
+        return Ok(false);
+    };
+    let first_page = octocrab::instance()
+        .issues(REPO_OWNER, REPO_NAME)
+        .list_timeline_events(issue_number)
+        .per_page(100)
+        .send()
+        .await?;
+    let events = octocrab::instance().all_pages(first_page).await?;
+    Ok(events.iter().any(|event| {
+        event.event == Event::Closed && event.commit_id.as_ref() == Some(merge_commit)
+    }))
+}
+
+/// Awards the open bounties on every issue closed by a newly confirmed bugfix PR
+pub async fn claim_bounties(db_conn: &DatabaseConnection, ctx: &Context, action: &actions::Model) {
+    if action.action_type != ActionType::PRFix {
+        return;
+    }
+    let now = Utc::now().timestamp();
+    let open_bounties: Vec<_> = match Bounties::find()
+        .filter(bounties::Column::ClaimedBy.is_null())
+        .order_by_asc(bounties::Column::Id)
+        .all(db_conn)
+        .await
+    {
+        Ok(b) => b.into_iter().filter(|b| b.is_open(now)).collect(),
+        Err(e) => {
+            log::error!("Error while fetching open bounties: {e:?}");
+            return;
+        }
+    };
+    if open_bounties.is_empty() {
+        return;
+    }
+    let Some(target) = GithubTarget::from_url(&action.github_link) else {
+        return;
+    };
+    let pr = match octocrab::instance()
+        .pulls(REPO_OWNER, REPO_NAME)
+        .get(target.issue_number())
+        .await
+    {
+        Ok(pr) => pr,
+        Err(e) => {
+            log::error!("Error while fetching PR of action {}: {e:?}", action.id);
+            return;
+        }
+    };
+    let user_ids = match load_action_users(db_conn, action).await {
+        Ok(user_ids) => user_ids,
+        Err(e) => {
+            log::error!(
+                "Error while fetching participants of action {}: {e:?}",
+                action.id
+            );
+            return;
+        }
+    };
+    let mentions = user_ids
+        .iter()
+        .map(|user_id| format!("<@{user_id}>"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut issue_numbers: Vec<u64> = open_bounties.iter().map(|b| b.issue_number).collect();
+    issue_numbers.sort();
+    issue_numbers.dedup();
+    for issue_number in issue_numbers {
+        // A PR merely mentioning the issue doesn't solve it
+        match closes_issue(&pr, issue_number).await {
+            Ok(true) => (),
+            Ok(false) => continue,
+            Err(e) => {
+                log::error!(
+                    "Error while checking if action {} closes #{issue_number}: {e:?}",
+                    action.id
+                );
+                continue;
+            }
+        }
+        for bounty in open_bounties
+            .iter()
+            .filter(|b| b.issue_number == issue_number)
+        {
+            let model = bounties::ActiveModel {
+                claimed_by: Set(Some(action.user_id.clone())),
+                claimed_action: Set(Some(action.id)),
+                claimed_at: Set(Some(now)),
+                ..Default::default()
+            };
+            // Only claims the bounty if nobody else did in the meantime
+            match Bounties::update_many()
+                .set(model)
+                .filter(bounties::Column::Id.eq(bounty.id))
+                .filter(bounties::Column::ClaimedBy.is_null())
+                .exec(db_conn)
+                .await
+            {
+                Ok(res) if res.rows_affected == 0 => continue,
+                Ok(_) => (),
+                Err(e) => {
+                    log::error!("Error while claiming bounty {}: {e:?}", bounty.id);
+                    continue;
+                }
+            }
+            if let Err(e) = CONFIG
+                .feed_channel
+                .send_message(
+                    &ctx.http,
+                    CreateMessage::new().embed(CreateEmbed::new().description(format!(
+                        "### 💰 {mentions} collected the bounty on [#{}](https://github.com/{REPO_OWNER}/{REPO_NAME}/issues/{}) ! +{} points",
+                        issue_number, issue_number, bounty.points
+                    ))),
+                )
+                .await
+            {
+                log::error!("Error while announcing claimed bounty {}: {e:?}", bounty.id);
+            }
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::Once;
+
+use chrono::{DateTime, Utc};
+use image::{ImageFormat, RgbImage};
+use plotters::prelude::*;
+use plotters::style::{FontStyle, register_font};
+use sea_orm::{DatabaseConnection, DbErr};
+use serenity::all::{Context, CreateAttachment};
+
+use crate::CONFIG;
+use crate::utils::contestants::{load_display_names, resolve_names};
+use crate::utils::ui::ScoreRows;
+
+/// Bundled so that charts render the same on any host, without system fonts
+static FONT: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans.ttf");
+static REGISTER_FONT: Once = Once::new();
+
+/// Name of the chart attachments, to reference them from embeds
+pub const CHART_FILENAME: &str = "points.png";
+/// Number of intervals the contest is split into, each line has one more point
+const SAMPLES: i64 = 50;
+const WIDTH: u32 = 1000;
+const HEIGHT: u32 = 600;
+
+/// The cumulative points of each user at regular times, from the start of the contest to now,
+/// or to the end of the review once it is over. The last point is their current total, also
+/// counting the submissions without a known creation date
+async fn load_points_history(
+    db_conn: &DatabaseConnection,
+    user_ids: &[String],
+) -> Result<HashMap<String, Vec<(i64, i64)>>, DbErr> {
+    let start = CONFIG.contest_start_timestamp;
+    let end = Utc::now().timestamp().min(CONFIG.review_end_timestamp);
+    let mut history: HashMap<String, Vec<(i64, i64)>> = HashMap::new();
+    if end <= start {
+        return Ok(history);
+    }
+    let rows = ScoreRows::load(db_conn).await?;
+    for i in 0..=SAMPLES {
+        let timestamp = start + (end - start) * i / SAMPLES;
+        let bounds = (i < SAMPLES).then_some((i64::MIN, timestamp + 1));
+        let score_map = rows.scores_in(bounds);
+        for user_id in user_ids {
+            let points = score_map
+                .get(user_id)
+                .map_or(0, |score| score.get_total_points());
+            history
+                .entry(user_id.clone())
+                .or_default()
+                .push((timestamp, points));
+        }
+    }
+    Ok(history)
+}
+
+/// A line chart of the cumulative points of users over the contest, as a PNG attachment
+pub async fn generate_points_chart(
+    db_conn: &DatabaseConnection,
+    ctx: &Context,
+    title: &str,
+    user_ids: &[String],
+) -> Result<CreateAttachment, String> {
+    let history = load_points_history(db_conn, user_ids)
+        .await
+        .map_err(|e| format!("Error while computing points history: {e:?}"))?;
+    let display_names = load_display_names(db_conn)
+        .await
+        .map_err(|e| format!("Error while fetching contestant display names: {e:?}"))?;
+    let names = resolve_names(ctx, &display_names, user_ids).await;
+    let series: Vec<_> = user_ids
+        .iter()
+        .filter_map(|user_id| Some((names.get(user_id)?.as_str(), history.get(user_id)?)))
+        .collect();
+    let png = render_chart(title, &series)?;
+    Ok(CreateAttachment::bytes(png, CHART_FILENAME))
+}
+
+/// Draws the labelled lines of points over time, encoded as a PNG image
+fn render_chart(title: &str, series: &[(&str, &Vec<(i64, i64)>)]) -> Result<Vec<u8>, String> {
+    REGISTER_FONT.call_once(|| {
+        if register_font("sans-serif", FontStyle::Normal, FONT).is_err() {
+            log::error!("Error while loading the bundled chart font");
+        }
+    });
+    let points = || series.iter().flat_map(|(_, line)| line.iter());
+    let (Some(start), Some(end)) = (
+        points().map(|(t, _)| *t).min(),
+        points().map(|(t, _)| *t).max(),
+    ) else {
+        return Err("No points to draw".to_string());
+    };
+    let min_points = points().map(|(_, p)| *p).min().unwrap_or(0).min(0);
+    let max_points = points().map(|(_, p)| *p).max().unwrap_or(0).max(1);
+
+    let mut buffer = vec![0; (WIDTH * HEIGHT * 3) as usize];
+    {
+        let root = BitMapBackend::with_buffer(&mut buffer, (WIDTH, HEIGHT)).into_drawing_area();
+        let draw_error = |e| format!("Error while drawing chart: {e:?}");
+        root.fill(&WHITE).map_err(draw_error)?;
+        let mut chart = ChartBuilder::on(&root)
+            .caption(title, ("sans-serif", 28))
+            .margin(20)
+            .x_label_area_size(40)
+            .y_label_area_size(50)
+            .build_cartesian_2d(start..end.max(start + 1), min_points..max_points)
+            .map_err(draw_error)?;
+        chart
+            .configure_mesh()
+            .x_labels(8)
+            .x_label_formatter(&|timestamp| {
+                DateTime::from_timestamp(*timestamp, 0)
+                    .map(|date| date.format("%b %d").to_string())
+                    .unwrap_or_default()
+            })
+            .y_desc("Points")
+            .draw()
+            .map_err(draw_error)?;
+        for (i, (label, line)) in series.iter().enumerate() {
+            let color = Palette99::pick(i).to_rgba();
+            chart
+                .draw_series(LineSeries::new(line.iter().copied(), color.stroke_width(3)))
+                .map_err(draw_error)?
+                .label(*label)
+                .legend(move |(x, y)| {
+                    PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(3))
+                });
+        }
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::UpperLeft)
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()
+            .map_err(draw_error)?;
+        root.present().map_err(draw_error)?;
+    }
+
+    let image = RgbImage::from_raw(WIDTH, HEIGHT, buffer)
+        .ok_or("Chart buffer doesn't match its size".to_string())?;
+    let mut png = Cursor::new(vec![]);
+    image
+        .write_to(&mut png, ImageFormat::Png)
+        .map_err(|e| format!("Error while encoding chart: {e:?}"))?;
+    Ok(png.into_inner())
+}
@@ -1,10 +1,158 @@
+use octocrab::models::IssueState;
 use serde::{Deserialize, Serialize};
-use serenity::all::{ChannelId, MessageId};
+use serenity::all::{ChannelId, MessageId, RoleId};
+
+use crate::entities::actions::ActionType;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Config {
     pub contest_start_timestamp: i64,
     pub contest_end_timestamp: i64,
+    /// End of the grace period during which items created before the end can still be submitted
+    pub submission_grace_end_timestamp: i64,
+    /// End of the review of the last submissions, after which the results are final
+    pub review_end_timestamp: i64,
     pub feed_channel: ChannelId,
     pub permanent_leaderboard: (ChannelId, MessageId),
+    /// Rules contestants must accept when running `/join`
+    pub contest_rules: String,
+    /// Whether users must `/join` the contest before submitting
+    pub require_registration: bool,
+    /// Role granted to users who join the contest
+    pub contestant_role: Option<RoleId>,
+    /// Words one of which a comment must contain to count as a bug confirmation, ignored when empty
+    pub confirmation_keywords: Vec<String>,
+    /// Points adjustments for submissions about issues with specific labels
+    pub label_bonuses: Vec<LabelBonus>,
+    /// Difficulty tiers offered to reviewers when confirming submissions of `tiered_action_types`
+    pub difficulty_tiers: Vec<DifficultyTier>,
+    pub tiered_action_types: Vec<ActionType>,
+    /// How the points of a submission with several participants are shared
+    pub credit_split: CreditSplit,
+    /// Timestamp after which team members can no longer leave or switch teams, unrestricted when unset
+    pub team_switch_deadline: Option<i64>,
+    /// Automatic penalty for denied submissions, disabled when unset
+    pub denied_penalty: Option<DeniedPenalty>,
+    /// Rules every submission of the matching types must pass
+    pub eligibility_rules: Vec<EligibilityRule>,
+    /// Seconds between two snapshots of the leaderboard
+    pub snapshot_interval: i64,
+    /// Rank movements on the leaderboards are shown since this many seconds ago
+    pub rank_movement_period: i64,
+    /// Roles given to the winners of each leaderboard category once the contest is finalized
+    pub winner_roles: Vec<WinnerRole>,
+    /// Roles held by the members whose confirmed points reach a threshold
+    pub point_roles: Vec<PointRole>,
+    /// Lengths in days of the daily streaks announced in the feed
+    pub daily_streak_milestones: Vec<u32>,
+    /// Lengths in weeks of the weekly streaks announced in the feed
+    pub weekly_streak_milestones: Vec<u32>,
+}
+
+/// Which GitHub item of a submission an [`EligibilityRule`] is checked against
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleSubject {
+    /// The submitted issue or PR, or the issue a submitted comment was posted on
+    Issue,
+    /// The bug issue a submitted PR fixes
+    LinkedIssue,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EligibilityRule {
+    /// Displayed to the user when a submission fails this rule
+    pub name: String,
+    pub action_types: Vec<ActionType>,
+    pub subject: RuleSubject,
+    /// Labels that must all be present
+    pub required_labels: Vec<String>,
+    /// Labels that must all be absent
+    pub forbidden_labels: Vec<String>,
+    /// Labels at least one of which must be present, ignored when empty
+    pub any_of_labels: Vec<String>,
+    /// Accepted states, ignored when empty
+    pub states: Vec<IssueState>,
+    /// GitHub logins whose items can't be submitted, such as bots
+    pub excluded_authors: Vec<String>,
+    /// Whether the item must have been written by the submitter's linked GitHub account
+    pub author_must_be_submitter: bool,
+}
+
+/// Adjusts the points of a submission whose issue carries `label`.
+/// Multipliers of several labels stack multiplicatively, bonuses are added after them.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LabelBonus {
+    pub label: String,
+    pub multiplier: f64,
+    pub bonus: i64,
+}
+
+/// A difficulty picked by the reviewer, whose points replace the base points of the submission
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DifficultyTier {
+    pub name: String,
+    pub emoji: char,
+    pub points: i64,
+}
+
+/// Points deducted for each denied submission of a user beyond the first `threshold` ones
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeniedPenalty {
+    pub threshold: u64,
+    pub points: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum CreditSplit {
+    /// Every participant gets the same share
+    Equal,
+    /// The submitter gets this fraction of the points, the rest is split equally between the others
+    SubmitterShare(f64),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WinnerRole {
+    /// Leaderboard category, the points category when unset
+    pub action_type: Option<ActionType>,
+    /// Number of ranks getting the role, tied users share a rank
+    pub top: u32,
+    pub role: RoleId,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PointRole {
+    /// Confirmed points from which the role is held
+    pub points: i64,
+    pub role: RoleId,
+}
+
+/// Achievements are read from `config/achievements.json`, so that they can be changed
+/// without rebuilding the bot
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Achievement {
+    /// Saved with the unlocks, must not change once users unlocked the achievement
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub emoji: char,
+    pub rule: AchievementRule,
+    /// Role granted to users who unlock the achievement
+    pub role: Option<RoleId>,
+}
+
+/// What a user must have done to unlock an [`Achievement`]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum AchievementRule {
+    /// At least `count` confirmed submissions, of any type when `action_type` is unset
+    Confirmed {
+        action_type: Option<ActionType>,
+        count: u64,
+    },
+    /// A confirmed bugfix PR created at most `within` seconds after the confirmed report of its issue
+    QuickFix { within: i64 },
+    /// Only unlocked by the participants of the confirmed submission of this type
+    /// created first on GitHub
+    FirstOfContest { action_type: ActionType },
+    /// At least `count` collected bounties
+    Bounties { count: u64 },
 }
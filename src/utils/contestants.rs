@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait};
+use serenity::all::{Context, UserId};
+
+use crate::CONFIG;
+use crate::entities::prelude::*;
+use crate::utils::ui::escape_markdown;
+
+/// Why a user can't submit, if they aren't a registered contestant in good standing
+pub async fn check_registration(
+    db_conn: &DatabaseConnection,
+    user_id: &str,
+) -> Result<Option<&'static str>, DbErr> {
+    Ok(match Contestants::find_by_id(user_id).one(db_conn).await? {
+        Some(contestant) if contestant.excluded => Some("You were excluded from the contest"),
+        Some(contestant) if contestant.accepted_at.is_some() => None,
+        _ if CONFIG.require_registration => {
+            Some("You must join the contest with `/join` before submitting !")
+        }
+        _ => None,
+    })
+}
+
+/// Why a user can't be credited as a participant of someone else's submission,
+/// like [`check_registration`] but also rejecting bots
+pub async fn check_participant(
+    db_conn: &DatabaseConnection,
+    ctx: &Context,
+    user_id: &str,
+) -> Result<Option<&'static str>, DbErr> {
+    let is_bot = match user_id.parse::<UserId>() {
+        Ok(id) => id.to_user(ctx).await.map(|user| user.bot),
+        Err(_) => return Ok(Some("isn't a Discord user")),
+    };
+    match is_bot {
+        Ok(true) => return Ok(Some("is a bot")),
+        Ok(false) => (),
+        Err(e) => {
+            log::debug!("Error while fetching user {user_id}: {e:?}");
+            return Ok(Some("isn't a Discord user"));
+        }
+    }
+    Ok(match Contestants::find_by_id(user_id).one(db_conn).await? {
+        Some(contestant) if contestant.excluded => Some("was excluded from the contest"),
+        Some(contestant) if contestant.accepted_at.is_some() => None,
+        _ if CONFIG.require_registration => Some("didn't join the contest"),
+        _ => None,
+    })
+}
+
+/// The display names contestants picked, by user id
+pub async fn load_display_names(
+    db_conn: &DatabaseConnection,
+) -> Result<HashMap<String, String>, DbErr> {
+    Ok(Contestants::find()
+        .all(db_conn)
+        .await?
+        .into_iter()
+        .filter_map(|contestant| Some((contestant.user_id, contestant.display_name?)))
+        .collect())
+}
+
+/// How a user is shown on the leaderboards, their display name or their mention
+pub fn user_label(display_names: &HashMap<String, String>, user_id: &str) -> String {
+    match display_names.get(user_id) {
+        Some(name) => format!("{} (<@{user_id}>)", escape_markdown(name)),
+        None => format!("<@{user_id}>"),
+    }
+}
+
+/// Plain text names of users, for images and files where mentions aren't rendered.
+/// Their display name, or their Discord username, or their id when they can't be fetched
+pub async fn resolve_names(
+    ctx: &Context,
+    display_names: &HashMap<String, String>,
+    user_ids: &[String],
+) -> HashMap<String, String> {
+    let mut names = HashMap::new();
+    for user_id in user_ids {
+        let name = match (display_names.get(user_id), user_id.parse::<UserId>()) {
+            (Some(name), _) => name.clone(),
+            (None, Ok(id)) => match id.to_user(ctx).await {
+                Ok(user) => user.name,
+                Err(e) => {
+                    log::error!("Error while fetching user {user_id}: {e:?}");
+                    user_id.clone()
+                }
+            },
+            (None, Err(_)) => user_id.clone(),
+        };
+        names.insert(user_id.clone(), name);
+    }
+    names
+}
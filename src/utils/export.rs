@@ -0,0 +1,240 @@
+use std::collections::{BTreeSet, HashMap};
+
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder};
+use serde_json::{Map, Value, json};
+use serenity::all::{Context, CreateAttachment};
+
+use crate::entities::actions::{self, ActionStatus, ActionType};
+use crate::entities::{final_standings, prelude::*};
+use crate::utils::contestants::{load_display_names, resolve_names};
+use crate::utils::participants::load_participants;
+use crate::utils::ui::{Score, category_title, compute_scores_in};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+/// Restricts the exported submissions, the date range also applies to the scores
+#[derive(Default)]
+pub struct ExportFilters {
+    pub status: Option<ActionStatus>,
+    pub action_type: Option<ActionType>,
+    /// Start and excluded end of the GitHub creation dates of the submissions
+    pub bounds: Option<(i64, i64)>,
+}
+
+/// Rows of exported data sharing the same columns
+pub struct Table {
+    name: &'static str,
+    columns: Vec<&'static str>,
+    rows: Vec<Vec<Value>>,
+}
+
+impl Table {
+    fn to_csv(&self) -> String {
+        let mut csv = self.columns.join(",") + "\n";
+        for row in &self.rows {
+            let fields: Vec<String> = row
+                .iter()
+                .map(|value| match value {
+                    Value::Null => String::new(),
+                    // Keeps spreadsheets from evaluating text that looks like a formula
+                    Value::String(s) if s.starts_with(['=', '+', '-', '@']) => {
+                        csv_escape(&format!("'{s}"))
+                    }
+                    Value::String(s) => csv_escape(s),
+                    value => csv_escape(&value.to_string()),
+                })
+                .collect();
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+
+    fn to_json(&self) -> String {
+        let objects: Vec<Value> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let object: Map<String, Value> = self
+                    .columns
+                    .iter()
+                    .map(|column| column.to_string())
+                    .zip(row.iter().cloned())
+                    .collect();
+                Value::Object(object)
+            })
+            .collect();
+        serde_json::to_string_pretty(&objects).unwrap_or_default()
+    }
+
+    pub fn into_attachment(self, format: ExportFormat) -> CreateAttachment {
+        match format {
+            ExportFormat::Csv => {
+                CreateAttachment::bytes(self.to_csv(), format!("{}.csv", self.name))
+            }
+            ExportFormat::Json => {
+                CreateAttachment::bytes(self.to_json(), format!("{}.json", self.name))
+            }
+        }
+    }
+}
+
+/// Quotes a CSV field when it contains a separator, a quote or a line break
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// The submissions matching the filters, the score breakdown of every user and the final
+/// rankings, with the names of the users next to their ids
+pub async fn export_tables(
+    db_conn: &DatabaseConnection,
+    ctx: &Context,
+    filters: &ExportFilters,
+) -> Result<Vec<Table>, DbErr> {
+    let mut query = Actions::find().order_by_asc(actions::Column::Id);
+    if let Some(status) = filters.status {
+        query = query.filter(actions::Column::ActionStatus.eq(status));
+    }
+    if let Some(action_type) = filters.action_type {
+        query = query.filter(actions::Column::ActionType.eq(action_type));
+    }
+    let actions: Vec<_> = query
+        .all(db_conn)
+        .await?
+        .into_iter()
+        .filter(|action| match (filters.bounds, action.github_created_at) {
+            (None, _) => true,
+            (Some((start, end)), Some(created_at)) => start <= created_at && created_at < end,
+            (Some(_), None) => false,
+        })
+        .collect();
+    let participants = load_participants(db_conn).await?;
+    let score_map = compute_scores_in(db_conn, filters.bounds).await?;
+    let standings = FinalStandings::find()
+        .order_by_asc(final_standings::Column::Id)
+        .all(db_conn)
+        .await?;
+
+    let user_ids: Vec<String> = actions
+        .iter()
+        .map(|action| action.user_id.clone())
+        .chain(participants.values().flatten().map(|p| p.user_id.clone()))
+        .chain(score_map.keys().cloned())
+        .chain(standings.iter().map(|s| s.user_id.clone()))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    // Display names are picked by the contestants, so they are kept apart from the usernames
+    let display_names = load_display_names(db_conn).await?;
+    let usernames = resolve_names(ctx, &HashMap::new(), &user_ids).await;
+    let names = |user_id: &str| {
+        [
+            json!(usernames.get(user_id).map_or(user_id, |name| name.as_str())),
+            json!(display_names.get(user_id)),
+        ]
+    };
+
+    let actions_table = Table {
+        name: "actions",
+        columns: vec![
+            "id",
+            "status",
+            "type",
+            "github_link",
+            "user_id",
+            "username",
+            "display_name",
+            "participants",
+            "linked_issue",
+            "points",
+            "event_points",
+            "difficulty",
+            "github_created_at",
+        ],
+        rows: actions
+            .iter()
+            .map(|action| {
+                // `user_id:share` pairs separated by semicolons
+                let shares = participants.get(&action.id).map(|participants| {
+                    participants
+                        .iter()
+                        .map(|p| format!("{}:{}", p.user_id, p.share))
+                        .collect::<Vec<_>>()
+                        .join(";")
+                });
+                let [username, display_name] = names(&action.user_id);
+                vec![
+                    json!(action.id),
+                    json!(format!("{:?}", action.action_status)),
+                    json!(format!("{:?}", action.action_type)),
+                    json!(action.github_link),
+                    json!(action.user_id),
+                    username,
+                    display_name,
+                    json!(shares),
+                    json!(action.linked_issue),
+                    json!(action.points),
+                    json!(action.event_points),
+                    json!(action.difficulty),
+                    json!(action.github_created_at),
+                ]
+            })
+            .collect(),
+    };
+
+    let mut scores: Vec<_> = score_map.iter().collect();
+    scores.sort_by_key(|(user_id, score)| (std::cmp::Reverse(score.get_total_points()), *user_id));
+    let mut score_columns = vec!["user_id", "username", "display_name"];
+    score_columns.extend(Score::default().get_fields().map(|(name, _)| name));
+    let scores_table = Table {
+        name: "scores",
+        columns: score_columns,
+        rows: scores
+            .into_iter()
+            .map(|(user_id, score)| {
+                let mut row = vec![json!(user_id)];
+                row.extend(names(user_id));
+                row.extend(score.get_fields().map(|(_, value)| json!(value)));
+                row
+            })
+            .collect(),
+    };
+
+    let rankings_table = Table {
+        name: "rankings",
+        columns: vec![
+            "category",
+            "rank",
+            "user_id",
+            "username",
+            "display_name",
+            "value",
+            "finalized_at",
+        ],
+        rows: standings
+            .iter()
+            .map(|standing| {
+                let [username, display_name] = names(&standing.user_id);
+                vec![
+                    json!(category_title(standing.action_type)),
+                    json!(standing.rank),
+                    json!(standing.user_id),
+                    username,
+                    display_name,
+                    json!(standing.value),
+                    json!(standing.finalized_at),
+                ]
+            })
+            .collect(),
+    };
+
+    Ok(vec![actions_table, scores_table, rankings_table])
+}
@@ -1,37 +1,596 @@
 use regex::Regex;
 use std::sync::LazyLock;
 
-#[derive(Debug)]
-pub struct IssueIds {
-    pub issue_id: u64,
-    pub comment_id: Option<u64>,
+use crate::entities::actions::ActionType;
+
+pub const REPO_OWNER: &str = "rh-hideout";
+pub const REPO_NAME: &str = "pokeemerald-expansion";
+
+/// A GitHub item of the contest repository, as pointed to by a submitted URL or short reference
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GithubTarget {
+    /// `/issues/N`
+    Issue { number: u64 },
+    /// `/issues/N#issuecomment-C`
+    IssueComment { number: u64, comment_id: u64 },
+    /// `/pull/N`, including its `/files`, `/commits`, `/checks` and `/changes` tabs
+    PullRequest { number: u64 },
+    /// `/pull/N#issuecomment-C`, a comment in the conversation tab of a PR
+    PullRequestComment { number: u64, comment_id: u64 },
+    /// `/pull/N#pullrequestreview-R`
+    PullRequestReview { number: u64, review_id: u64 },
+    /// `/pull/N#discussion_rC` or `/pull/N/files#rC`, a comment on the diff of a PR
+    ReviewComment { number: u64, comment_id: u64 },
+    /// `#N`, `pokeemerald-expansion#N` or `rh-hideout/pokeemerald-expansion#N`,
+    /// which can be either an issue or a PR
+    Reference { number: u64 },
 }
 
-static COMMENT_RE: LazyLock<Regex> = LazyLock::new(|| {
-    Regex::new(r"https?://github\.com/rh-hideout/pokeemerald-expansion/issues/(?<issue_id>\d+)\#issuecomment-(?<comment_id>\d+)").unwrap()
-});
-static ISSUE_RE: LazyLock<Regex> = LazyLock::new(|| {
+static URL_RE: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(
-        r"https?://github\.com/rh-hideout/pokeemerald-expansion/(issues|pull)/(?<issue_id>\d+)",
+        r"^(?i:https?://)?(?i:www\.)?(?i:github\.com)/(?i:rh-hideout/pokeemerald-expansion)/(?<kind>issues|pull)/(?<number>\d+)(?:/(?<tab>files|commits|checks|changes))?/?(?:\?[^#]*)?(?:#(?<anchor>.*))?$",
     )
     .unwrap()
 });
+static REFERENCE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?i:(?:rh-hideout/)?pokeemerald-expansion)?#(?<number>\d+)$").unwrap()
+});
+static ANCHOR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?<kind>issuecomment-|discussion_r|pullrequestreview-|r)(?<id>\d+)$").unwrap()
+});
+static CLOSING_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:close[sd]?|fix(?:e[sd])?|resolve[sd]?):?\s+(?<reference>\S+)").unwrap()
+});
 
-impl IssueIds {
+/// Numbers of the issues a PR body closes through GitHub's closing keywords (`Fixes #123`)
+pub fn closing_references(body: &str) -> Vec<u64> {
+    let mut numbers = Vec::new();
+    for caps in CLOSING_RE.captures_iter(body) {
+        let reference = caps["reference"].trim_end_matches(['.', ',', ';', ':', ')', '!']);
+        if let Some(target @ (GithubTarget::Issue { .. } | GithubTarget::Reference { .. })) =
+            GithubTarget::from_url(reference)
+            && !numbers.contains(&target.issue_number())
+        {
+            numbers.push(target.issue_number());
+        }
+    }
+    numbers
+}
+
+impl GithubTarget {
     pub fn from_url(url: impl Into<String>) -> Option<Self> {
-        let str_url = &url.into();
-        if let Some(caps) = COMMENT_RE.captures(str_url) {
-            return Some(Self {
-                issue_id: caps["issue_id"].parse().unwrap(),
-                comment_id: Some(caps["comment_id"].parse().unwrap()),
-            });
-        } else if let Some(caps) = ISSUE_RE.captures(str_url) {
-            return Some(Self {
-                issue_id: caps["issue_id"].parse().unwrap(),
-                comment_id: None,
+        let str_url: String = url.into();
+        // Discord users wrap links in <> to suppress embeds
+        let input = str_url
+            .trim()
+            .trim_start_matches('<')
+            .trim_end_matches('>')
+            .trim();
+
+        if let Some(caps) = REFERENCE_RE.captures(input) {
+            return Some(Self::Reference {
+                number: caps["number"].parse().ok()?,
             });
-        } else {
+        }
+
+        let caps = URL_RE.captures(input)?;
+        let number: u64 = caps["number"].parse().ok()?;
+        let is_pull = &caps["kind"] == "pull";
+        if !is_pull && caps.name("tab").is_some() {
             return None;
         }
+
+        let anchor = match caps.name("anchor").map(|a| a.as_str()) {
+            None | Some("") => None,
+            Some(a) => ANCHOR_RE
+                .captures(a)
+                .and_then(|c| Some((c.name("kind")?.as_str(), c["id"].parse::<u64>().ok()?))),
+        };
+
+        match (is_pull, anchor) {
+            (false, None) => Some(Self::Issue { number }),
+            (false, Some(("issuecomment-", comment_id))) => {
+                Some(Self::IssueComment { number, comment_id })
+            }
+            (false, Some(_)) => None,
+            (true, None) => Some(Self::PullRequest { number }),
+            (true, Some(("issuecomment-", comment_id))) => {
+                Some(Self::PullRequestComment { number, comment_id })
+            }
+            (true, Some(("pullrequestreview-", review_id))) => {
+                Some(Self::PullRequestReview { number, review_id })
+            }
+            (true, Some((_, comment_id))) => Some(Self::ReviewComment { number, comment_id }),
+        }
+    }
+
+    /// The number of the issue or PR this target belongs to
+    pub fn issue_number(&self) -> u64 {
+        match *self {
+            Self::Issue { number }
+            | Self::IssueComment { number, .. }
+            | Self::PullRequest { number }
+            | Self::PullRequestComment { number, .. }
+            | Self::PullRequestReview { number, .. }
+            | Self::ReviewComment { number, .. }
+            | Self::Reference { number } => number,
+        }
+    }
+
+    /// The id of the issue comment this target points to, if any
+    pub fn comment_id(&self) -> Option<u64> {
+        match *self {
+            Self::IssueComment { comment_id, .. } | Self::PullRequestComment { comment_id, .. } => {
+                Some(comment_id)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether this kind of target can be submitted for the given submission type.
+    /// Whether the issue is actually a PR still has to be checked against the GitHub API.
+    pub fn accepts(&self, action_type: ActionType) -> bool {
+        match action_type {
+            ActionType::ReportBug => matches!(self, Self::Issue { .. } | Self::Reference { .. }),
+            ActionType::ConfirmBug => matches!(self, Self::IssueComment { .. }),
+            ActionType::PRFix => matches!(
+                self,
+                Self::Issue { .. }
+                    | Self::PullRequest { .. }
+                    | Self::PullRequestComment { .. }
+                    | Self::PullRequestReview { .. }
+                    | Self::ReviewComment { .. }
+                    | Self::Reference { .. }
+            ),
+        }
+    }
+
+    /// The target recorded for a submission of the given type, as a bugfix PR submitted
+    /// through one of its comments or reviews is still submitted as a whole
+    pub fn submitted_for(&self, action_type: ActionType) -> Self {
+        match (action_type, *self) {
+            (
+                ActionType::PRFix,
+                Self::PullRequestComment { number, .. }
+                | Self::PullRequestReview { number, .. }
+                | Self::ReviewComment { number, .. },
+            ) => Self::PullRequest { number },
+            (_, target) => target,
+        }
+    }
+
+    /// The single URL stored for this target, so that the same item submitted
+    /// through different links is still detected as a duplicate
+    pub fn canonical_url(&self, is_pull_request: bool) -> String {
+        let kind = if is_pull_request { "pull" } else { "issues" };
+        let base = format!(
+            "https://github.com/{REPO_OWNER}/{REPO_NAME}/{kind}/{}",
+            self.issue_number()
+        );
+        match *self {
+            Self::Issue { .. } | Self::PullRequest { .. } | Self::Reference { .. } => base,
+            Self::IssueComment { comment_id, .. } | Self::PullRequestComment { comment_id, .. } => {
+                format!("{base}#issuecomment-{comment_id}")
+            }
+            Self::PullRequestReview { review_id, .. } => {
+                format!("{base}#pullrequestreview-{review_id}")
+            }
+            Self::ReviewComment { comment_id, .. } => format!("{base}#discussion_r{comment_id}"),
+        }
+    }
+}
+
+/// The canonical form of a link stored for a submission of the given type, as only bugfixes
+/// are submitted as PRs. Used to rewrite the links stored before they were canonicalized
+pub fn canonical_link(link: &str, action_type: ActionType) -> Option<String> {
+    let target = GithubTarget::from_url(link)?.submitted_for(action_type);
+    Some(target.canonical_url(action_type == ActionType::PRFix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "https://github.com/rh-hideout/pokeemerald-expansion";
+
+    fn parse(s: &str) -> Option<GithubTarget> {
+        GithubTarget::from_url(s)
+    }
+
+    #[test]
+    fn parses_issue_urls() {
+        let expected = Some(GithubTarget::Issue { number: 1234 });
+        assert_eq!(parse(&format!("{BASE}/issues/1234")), expected);
+        assert_eq!(parse(&format!("{BASE}/issues/1234/")), expected);
+        assert_eq!(parse(&format!("{BASE}/issues/1234?foo=bar")), expected);
+        assert_eq!(parse(&format!("{BASE}/issues/1234#issue-987654")), expected);
+        assert_eq!(parse(&format!("{BASE}/issues/1234#")), expected);
+        assert_eq!(
+            parse("http://github.com/rh-hideout/pokeemerald-expansion/issues/1234"),
+            expected
+        );
+        assert_eq!(
+            parse("https://www.github.com/rh-hideout/pokeemerald-expansion/issues/1234"),
+            expected
+        );
+        assert_eq!(
+            parse("github.com/rh-hideout/pokeemerald-expansion/issues/1234"),
+            expected
+        );
+        assert_eq!(
+            parse("https://GitHub.com/RH-Hideout/pokeemerald-expansion/issues/1234"),
+            expected
+        );
+    }
+
+    #[test]
+    fn parses_issue_comment_urls() {
+        let expected = Some(GithubTarget::IssueComment {
+            number: 1234,
+            comment_id: 3012345678,
+        });
+        assert_eq!(
+            parse(&format!("{BASE}/issues/1234#issuecomment-3012345678")),
+            expected
+        );
+        assert_eq!(
+            parse(&format!("{BASE}/issues/1234?q=1#issuecomment-3012345678")),
+            expected
+        );
+    }
+
+    #[test]
+    fn parses_pull_request_urls() {
+        let expected = Some(GithubTarget::PullRequest { number: 42 });
+        assert_eq!(parse(&format!("{BASE}/pull/42")), expected);
+        assert_eq!(parse(&format!("{BASE}/pull/42/")), expected);
+        assert_eq!(parse(&format!("{BASE}/pull/42/files")), expected);
+        assert_eq!(parse(&format!("{BASE}/pull/42/files/")), expected);
+        assert_eq!(parse(&format!("{BASE}/pull/42/commits")), expected);
+        assert_eq!(parse(&format!("{BASE}/pull/42/checks")), expected);
+        assert_eq!(parse(&format!("{BASE}/pull/42/changes")), expected);
+        assert_eq!(parse(&format!("{BASE}/pull/42/files?diff=split")), expected);
+    }
+
+    #[test]
+    fn parses_pull_request_conversation_comments() {
+        assert_eq!(
+            parse(&format!("{BASE}/pull/42#issuecomment-555")),
+            Some(GithubTarget::PullRequestComment {
+                number: 42,
+                comment_id: 555
+            })
+        );
+    }
+
+    #[test]
+    fn parses_pull_request_reviews() {
+        assert_eq!(
+            parse(&format!("{BASE}/pull/42#pullrequestreview-777")),
+            Some(GithubTarget::PullRequestReview {
+                number: 42,
+                review_id: 777
+            })
+        );
+    }
+
+    #[test]
+    fn parses_review_comments() {
+        let expected = Some(GithubTarget::ReviewComment {
+            number: 42,
+            comment_id: 999,
+        });
+        assert_eq!(parse(&format!("{BASE}/pull/42#discussion_r999")), expected);
+        assert_eq!(parse(&format!("{BASE}/pull/42/files#r999")), expected);
+        assert_eq!(
+            parse(&format!("{BASE}/pull/42/files#discussion_r999")),
+            expected
+        );
+    }
+
+    #[test]
+    fn parses_short_references() {
+        let expected = Some(GithubTarget::Reference { number: 1234 });
+        assert_eq!(parse("#1234"), expected);
+        assert_eq!(parse("pokeemerald-expansion#1234"), expected);
+        assert_eq!(parse("rh-hideout/pokeemerald-expansion#1234"), expected);
+        assert_eq!(parse("  #1234 "), expected);
+    }
+
+    #[test]
+    fn strips_embed_suppression_brackets() {
+        assert_eq!(
+            parse(&format!("<{BASE}/issues/1234>")),
+            Some(GithubTarget::Issue { number: 1234 })
+        );
+        assert_eq!(
+            parse(&format!(" <{BASE}/pull/42/files> ")),
+            Some(GithubTarget::PullRequest { number: 42 })
+        );
+    }
+
+    #[test]
+    fn ignores_unknown_anchors() {
+        assert_eq!(
+            parse(&format!("{BASE}/pull/42#event-123")),
+            Some(GithubTarget::PullRequest { number: 42 })
+        );
+        assert_eq!(
+            parse(&format!("{BASE}/pull/42#discussion_rabc")),
+            Some(GithubTarget::PullRequest { number: 42 })
+        );
+        assert_eq!(
+            parse(&format!("{BASE}/issues/7#ref-commit-abc")),
+            Some(GithubTarget::Issue { number: 7 })
+        );
+    }
+
+    #[test]
+    fn rejects_pull_request_only_shapes_on_issues() {
+        assert_eq!(parse(&format!("{BASE}/issues/1234/files")), None);
+        assert_eq!(parse(&format!("{BASE}/issues/1234#discussion_r999")), None);
+        assert_eq!(
+            parse(&format!("{BASE}/issues/1234#pullrequestreview-777")),
+            None
+        );
+    }
+
+    #[test]
+    fn rejects_other_repositories() {
+        assert_eq!(
+            parse("https://github.com/pret/pokeemerald/issues/1234"),
+            None
+        );
+        assert_eq!(
+            parse("https://github.com/rh-hideout/pokeemerald-expansion-fork/issues/1234"),
+            None
+        );
+        assert_eq!(
+            parse("https://github.com/someone/pokeemerald-expansion/pull/42"),
+            None
+        );
+        assert_eq!(parse("pokeemerald#1234"), None);
+        assert_eq!(parse("someone/pokeemerald-expansion#1234"), None);
+        assert_eq!(
+            parse("https://gitlab.com/rh-hideout/pokeemerald-expansion/issues/1234"),
+            None
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("#"), None);
+        assert_eq!(parse("1234"), None);
+        assert_eq!(parse("#abc"), None);
+        assert_eq!(parse("# 1234"), None);
+        assert_eq!(parse(BASE), None);
+        assert_eq!(parse(&format!("{BASE}/issues")), None);
+        assert_eq!(parse(&format!("{BASE}/issues/abc")), None);
+        assert_eq!(parse(&format!("{BASE}/discussions/12")), None);
+        assert_eq!(parse(&format!("{BASE}/pull/42/files/extra")), None);
+        assert_eq!(parse(&format!("see {BASE}/issues/1234")), None);
+        assert_eq!(parse(&format!("{BASE}/issues/1234 thanks")), None);
+        assert_eq!(
+            parse(&format!("{BASE}/issues/99999999999999999999999")),
+            None
+        );
+        assert_eq!(parse("#99999999999999999999999"), None);
+    }
+
+    #[test]
+    fn exposes_issue_number_and_comment_id() {
+        let comment = GithubTarget::IssueComment {
+            number: 1,
+            comment_id: 2,
+        };
+        assert_eq!(comment.issue_number(), 1);
+        assert_eq!(comment.comment_id(), Some(2));
+        let pr_comment = GithubTarget::PullRequestComment {
+            number: 3,
+            comment_id: 4,
+        };
+        assert_eq!(pr_comment.issue_number(), 3);
+        assert_eq!(pr_comment.comment_id(), Some(4));
+        let review = GithubTarget::PullRequestReview {
+            number: 5,
+            review_id: 6,
+        };
+        assert_eq!(review.issue_number(), 5);
+        assert_eq!(review.comment_id(), None);
+        assert_eq!(GithubTarget::Reference { number: 8 }.issue_number(), 8);
+    }
+
+    #[test]
+    fn maps_kinds_to_submission_types() {
+        use ActionType::*;
+        let cases = [
+            (GithubTarget::Issue { number: 1 }, [true, false, true]),
+            (
+                GithubTarget::IssueComment {
+                    number: 1,
+                    comment_id: 1,
+                },
+                [false, true, false],
+            ),
+            (
+                GithubTarget::PullRequest { number: 1 },
+                [false, false, true],
+            ),
+            (
+                GithubTarget::PullRequestComment {
+                    number: 1,
+                    comment_id: 1,
+                },
+                [false, false, true],
+            ),
+            (
+                GithubTarget::PullRequestReview {
+                    number: 1,
+                    review_id: 1,
+                },
+                [false, false, true],
+            ),
+            (
+                GithubTarget::ReviewComment {
+                    number: 1,
+                    comment_id: 1,
+                },
+                [false, false, true],
+            ),
+            (GithubTarget::Reference { number: 1 }, [true, false, true]),
+        ];
+        for (target, [report, confirm, fix]) in cases {
+            assert_eq!(target.accepts(ReportBug), report, "{target:?} ReportBug");
+            assert_eq!(target.accepts(ConfirmBug), confirm, "{target:?} ConfirmBug");
+            assert_eq!(target.accepts(PRFix), fix, "{target:?} PRFix");
+        }
+    }
+
+    #[test]
+    fn canonicalizes_stored_links() {
+        assert_eq!(
+            canonical_link(
+                &format!("<http://github.com/{REPO_OWNER}/{REPO_NAME}/pull/42/files>"),
+                ActionType::PRFix
+            ),
+            Some(format!("{BASE}/pull/42"))
+        );
+        assert_eq!(
+            canonical_link(&format!("{BASE}/issues/42"), ActionType::PRFix),
+            Some(format!("{BASE}/pull/42"))
+        );
+        assert_eq!(
+            canonical_link(&format!("{BASE}/pull/42#discussion_r7"), ActionType::PRFix),
+            Some(format!("{BASE}/pull/42"))
+        );
+        assert_eq!(
+            canonical_link("#12", ActionType::ReportBug),
+            Some(format!("{BASE}/issues/12"))
+        );
+        assert_eq!(
+            canonical_link(
+                &format!("{BASE}/issues/12/#issuecomment-34"),
+                ActionType::ConfirmBug
+            ),
+            Some(format!("{BASE}/issues/12#issuecomment-34"))
+        );
+        assert_eq!(canonical_link("not a link", ActionType::ReportBug), None);
+    }
+
+    #[test]
+    fn builds_canonical_urls() {
+        assert_eq!(
+            GithubTarget::Reference { number: 12 }.canonical_url(false),
+            format!("{BASE}/issues/12")
+        );
+        assert_eq!(
+            GithubTarget::Reference { number: 12 }.canonical_url(true),
+            format!("{BASE}/pull/12")
+        );
+        assert_eq!(
+            GithubTarget::Issue { number: 12 }.canonical_url(true),
+            format!("{BASE}/pull/12")
+        );
+        assert_eq!(
+            parse(&format!("<{BASE}/pull/12/files?w=1>"))
+                .unwrap()
+                .canonical_url(true),
+            format!("{BASE}/pull/12")
+        );
+        assert_eq!(
+            parse(&format!("{BASE}/issues/12?x#issuecomment-34"))
+                .unwrap()
+                .canonical_url(false),
+            format!("{BASE}/issues/12#issuecomment-34")
+        );
+        assert_eq!(
+            parse(&format!("{BASE}/pull/12/files#r56"))
+                .unwrap()
+                .canonical_url(true),
+            format!("{BASE}/pull/12#discussion_r56")
+        );
+        assert_eq!(
+            parse(&format!("{BASE}/pull/12#pullrequestreview-78"))
+                .unwrap()
+                .canonical_url(true),
+            format!("{BASE}/pull/12#pullrequestreview-78")
+        );
+    }
+
+    #[test]
+    fn submits_bugfix_comments_as_their_pr() {
+        for url in [
+            format!("{BASE}/pull/12#issuecomment-34"),
+            format!("{BASE}/pull/12#pullrequestreview-78"),
+            format!("{BASE}/pull/12/files#r56"),
+            format!("{BASE}/pull/12#discussion_r56"),
+        ] {
+            let target = parse(&url).unwrap().submitted_for(ActionType::PRFix);
+            assert_eq!(target, GithubTarget::PullRequest { number: 12 }, "{url}");
+            assert_eq!(target.canonical_url(true), format!("{BASE}/pull/12"));
+        }
+        let comment = GithubTarget::IssueComment {
+            number: 12,
+            comment_id: 34,
+        };
+        assert_eq!(comment.submitted_for(ActionType::ConfirmBug), comment);
+    }
+
+    #[test]
+    fn canonical_urls_parse_back_to_the_same_target() {
+        let targets = [
+            GithubTarget::Issue { number: 1 },
+            GithubTarget::IssueComment {
+                number: 1,
+                comment_id: 2,
+            },
+            GithubTarget::PullRequest { number: 3 },
+            GithubTarget::PullRequestComment {
+                number: 3,
+                comment_id: 4,
+            },
+            GithubTarget::PullRequestReview {
+                number: 3,
+                review_id: 5,
+            },
+            GithubTarget::ReviewComment {
+                number: 3,
+                comment_id: 6,
+            },
+        ];
+        for target in targets {
+            let is_pull = !matches!(
+                target,
+                GithubTarget::Issue { .. } | GithubTarget::IssueComment { .. }
+            );
+            assert_eq!(parse(&target.canonical_url(is_pull)), Some(target));
+        }
+    }
+
+    #[test]
+    fn finds_closing_references() {
+        assert_eq!(closing_references("Fixes #123"), vec![123]);
+        assert_eq!(
+            closing_references("closes #1, resolved: #2.\nAlso FIXED #3 and fixes #1"),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            closing_references(&format!("Fix {BASE}/issues/45 (thanks!)")),
+            vec![45]
+        );
+        assert_eq!(
+            closing_references("Resolves rh-hideout/pokeemerald-expansion#67"),
+            vec![67]
+        );
+    }
+
+    #[test]
+    fn ignores_non_closing_references() {
+        assert!(closing_references("Related to #123").is_empty());
+        assert!(closing_references("Prefix #12 and suffixes #13").is_empty());
+        assert!(closing_references("Fixes pret/pokeemerald#123").is_empty());
+        assert!(closing_references(&format!("Fixes {BASE}/pull/12")).is_empty());
+        assert!(closing_references("Fixes the crash").is_empty());
     }
 }
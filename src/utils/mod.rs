@@ -1,3 +1,21 @@
+pub mod achievements;
+pub mod activity;
+pub mod bounties;
+pub mod charts;
 pub mod config;
+pub mod contestants;
+pub mod export;
 pub mod issues;
+pub mod participants;
+pub mod penalties;
+pub mod permissions;
+pub mod phase;
+pub mod point_roles;
+pub mod points;
+pub mod results;
+pub mod snapshots;
+pub mod streaks;
+pub mod teams;
+pub mod time;
 pub mod ui;
+pub mod validation;
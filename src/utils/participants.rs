@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use sea_orm::{
+    ActiveValue, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter,
+};
+
+use crate::CONFIG;
+use crate::entities::{action_participants, actions, prelude::*};
+use crate::utils::config::CreditSplit;
+use crate::utils::issues::{REPO_NAME, REPO_OWNER};
+
+static CO_AUTHOR_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?im)^\s*co-authored-by:[^<\n]*<(?<email>[^>\n]+)>").unwrap());
+static NOREPLY_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^(?:\d+\+)?(?<login>[^@]+)@users\.noreply\.github\.com$").unwrap()
+});
+static MENTION_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<@!?(?<id>\d+)>").unwrap());
+
+/// Discord user ids mentioned in a string typed by the user
+pub fn parse_mentions(input: &str) -> Vec<String> {
+    let mut ids: Vec<String> = Vec::new();
+    for caps in MENTION_RE.captures_iter(input) {
+        if !ids.contains(&caps["id"].to_string()) {
+            ids.push(caps["id"].to_string());
+        }
+    }
+    ids
+}
+
+/// Finds the users with a linked GitHub account who authored the commits of a PR or are
+/// credited in their `Co-authored-by` trailers. Trailers are only matched by noreply email,
+/// as their names can be set to anything
+pub async fn detect_co_authors(
+    db_conn: &DatabaseConnection,
+    pr_number: u64,
+) -> Result<Vec<String>, String> {
+    let commits = octocrab::instance()
+        .pulls(REPO_OWNER, REPO_NAME)
+        .pr_commits(pr_number)
+        .per_page(100)
+        .send()
+        .await
+        .map_err(|e| format!("{e:?}"))?;
+    let accounts = LinkedAccounts::find()
+        .all(db_conn)
+        .await
+        .map_err(|e| format!("{e:?}"))?;
+
+    let mut user_ids: Vec<String> = Vec::new();
+    for commit in commits.items {
+        let logins = commit.author.map(|author| author.login).into_iter().chain(
+            CO_AUTHOR_RE
+                .captures_iter(&commit.commit.message)
+                .filter_map(|caps| Some(NOREPLY_RE.captures(&caps["email"])?["login"].to_string())),
+        );
+        for login in logins {
+            if let Some(account) = accounts
+                .iter()
+                .find(|account| account.github_login.eq_ignore_ascii_case(&login))
+                && !user_ids.contains(&account.user_id)
+            {
+                user_ids.push(account.user_id.clone());
+            }
+        }
+    }
+    Ok(user_ids)
+}
+
+/// Splits the credit of a submission between its submitter and the other participants,
+/// following the configured rule
+pub fn split_shares(submitter: &str, others: &[String]) -> Vec<(String, f64)> {
+    split_shares_with(CONFIG.credit_split, submitter, others)
+}
+
+fn split_shares_with(
+    credit_split: CreditSplit,
+    submitter: &str,
+    others: &[String],
+) -> Vec<(String, f64)> {
+    let others: Vec<&String> = others.iter().filter(|o| *o != submitter).collect();
+    if others.is_empty() {
+        return vec![(submitter.to_string(), 1.0)];
+    }
+    let participant_count = others.len() as f64 + 1.0;
+    let submitter_share = match credit_split {
+        CreditSplit::Equal => 1.0 / participant_count,
+        CreditSplit::SubmitterShare(share) => share,
+    };
+    let other_share = (1.0 - submitter_share) / (participant_count - 1.0);
+    std::iter::once((submitter.to_string(), submitter_share))
+        .chain(others.into_iter().map(|o| (o.clone(), other_share)))
+        .collect()
+}
+
+/// Splits `points` between participants following their shares, in whole points adding up
+/// to `points`. What is lost by rounding the shares down goes to the submitter
+pub fn split_points(points: i64, submitter: &str, shares: &[(String, f64)]) -> Vec<(String, i64)> {
+    // Keeps exact shares such as 3 x 1/3 from being rounded down because of float errors
+    let mut split: Vec<(String, i64)> = shares
+        .iter()
+        .map(|(user_id, share)| {
+            (
+                user_id.clone(),
+                (points as f64 * share + 1e-9).floor() as i64,
+            )
+        })
+        .collect();
+    let remainder = points - split.iter().map(|(_, points)| points).sum::<i64>();
+    match split
+        .iter_mut()
+        .position(|(user_id, _)| user_id == submitter)
+    {
+        Some(i) => split[i].1 += remainder,
+        None if split.is_empty() => split.push((submitter.to_string(), remainder)),
+        None => split[0].1 += remainder,
+    }
+    split
+}
+
+/// Records the participants of a newly inserted submission, in the transaction inserting it
+pub async fn insert_participants(
+    db_conn: &impl ConnectionTrait,
+    action_id: u32,
+    shares: &[(String, f64)],
+) -> Result<(), DbErr> {
+    if shares.len() < 2 {
+        return Ok(());
+    }
+    ActionParticipants::insert_many(shares.iter().map(|(user_id, share)| {
+        action_participants::ActiveModel {
+            id: ActiveValue::NotSet,
+            action_id: ActiveValue::Set(action_id),
+            user_id: ActiveValue::Set(user_id.clone()),
+            share: ActiveValue::Set(*share),
+        }
+    }))
+    .exec(db_conn)
+    .await?;
+    Ok(())
+}
+
+/// The participants of every shared submission, by action id
+pub async fn load_participants(
+    db_conn: &DatabaseConnection,
+) -> Result<HashMap<u32, Vec<action_participants::Model>>, DbErr> {
+    let mut participants: HashMap<u32, Vec<action_participants::Model>> = HashMap::new();
+    for participant in ActionParticipants::find().all(db_conn).await? {
+        participants
+            .entry(participant.action_id)
+            .or_default()
+            .push(participant);
+    }
+    Ok(participants)
+}
+
+/// Everyone credited for a submission, its submitter when it isn't shared
+pub async fn load_action_users(
+    db_conn: &DatabaseConnection,
+    action: &actions::Model,
+) -> Result<Vec<String>, DbErr> {
+    let participants = ActionParticipants::find()
+        .filter(action_participants::Column::ActionId.eq(action.id))
+        .all(db_conn)
+        .await?;
+    Ok(if participants.is_empty() {
+        vec![action.user_id.clone()]
+    } else {
+        participants.into_iter().map(|p| p.user_id).collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn splits_shares_equally() {
+        assert_eq!(
+            split_shares_with(CreditSplit::Equal, "a", &[]),
+            vec![("a".to_string(), 1.0)]
+        );
+        // The submitter isn't counted twice
+        let shares = split_shares_with(CreditSplit::Equal, "a", &ids(&["b", "a", "c"]));
+        assert_eq!(shares.len(), 3);
+        assert_eq!(shares[0].0, "a");
+        assert!(
+            shares
+                .iter()
+                .all(|(_, share)| (share - 1.0 / 3.0).abs() < 1e-9)
+        );
+    }
+
+    #[test]
+    fn splits_shares_around_the_submitter() {
+        let shares = split_shares_with(CreditSplit::SubmitterShare(0.5), "a", &ids(&["b", "c"]));
+        assert_eq!(
+            shares,
+            vec![
+                ("a".to_string(), 0.5),
+                ("b".to_string(), 0.25),
+                ("c".to_string(), 0.25)
+            ]
+        );
+    }
+
+    #[test]
+    fn split_points_add_up() {
+        let shares = split_shares_with(CreditSplit::Equal, "a", &ids(&["b", "c"]));
+        assert_eq!(
+            split_points(5, "a", &shares),
+            vec![
+                ("a".to_string(), 3),
+                ("b".to_string(), 1),
+                ("c".to_string(), 1)
+            ]
+        );
+        assert_eq!(
+            split_points(6, "a", &shares),
+            vec![
+                ("a".to_string(), 2),
+                ("b".to_string(), 2),
+                ("c".to_string(), 2)
+            ]
+        );
+        let shares = split_shares_with(
+            CreditSplit::SubmitterShare(0.6),
+            "a",
+            &ids(&["b", "c", "d"]),
+        );
+        for points in [-7, 0, 1, 2, 5, 7, 13, 100] {
+            let split = split_points(points, "a", &shares);
+            assert_eq!(split.iter().map(|(_, p)| p).sum::<i64>(), points);
+        }
+        assert_eq!(split_points(4, "a", &[]), vec![("a".to_string(), 4)]);
+    }
+}
@@ -0,0 +1,50 @@
+use chrono::Utc;
+use sea_orm::{
+    ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
+};
+
+use crate::CONFIG;
+use crate::entities::actions::{self, ActionStatus};
+use crate::entities::{penalties, prelude::*};
+
+/// Applies the configured penalty for a newly denied submission,
+/// once its author has more denied submissions than the threshold
+pub async fn apply_denied_penalty(db_conn: &DatabaseConnection, action: &actions::Model) {
+    let Some(denied_penalty) = &CONFIG.denied_penalty else {
+        return;
+    };
+    let denied_count = match Actions::find()
+        .filter(actions::Column::UserId.eq(&action.user_id))
+        .filter(actions::Column::ActionStatus.eq(ActionStatus::Denied))
+        .count(db_conn)
+        .await
+    {
+        Ok(c) => c,
+        Err(e) => {
+            log::error!("Error while counting denied submissions: {e:?}");
+            return;
+        }
+    };
+    if denied_count <= denied_penalty.threshold {
+        return;
+    }
+
+    let penalty = penalties::ActiveModel {
+        id: ActiveValue::NotSet,
+        user_id: ActiveValue::Set(action.user_id.clone()),
+        points: ActiveValue::Set(denied_penalty.points),
+        reason: ActiveValue::Set(format!(
+            "Denied {} ({denied_count} denied submissions)",
+            action.action_type
+        )),
+        issued_by: ActiveValue::Set(None),
+        action_id: ActiveValue::Set(Some(action.id)),
+        created_at: ActiveValue::Set(Utc::now().timestamp()),
+    };
+    if let Err(e) = Penalties::insert(penalty).exec(db_conn).await {
+        log::error!(
+            "Error while applying penalty for action {}: {e:?}",
+            action.id
+        );
+    }
+}
@@ -0,0 +1,28 @@
+use serenity::all::{CommandInteraction, Context, EditInteractionResponse};
+
+use crate::EXPANSION_SENATE_ROLE;
+
+/// Whether the author of a command is an expansion senate member
+pub fn is_senate_member(command: &CommandInteraction) -> bool {
+    command
+        .member
+        .as_ref()
+        .is_some_and(|member| member.roles.contains(&EXPANSION_SENATE_ROLE))
+}
+
+/// Checks that the author of a deferred command is an expansion senate member,
+/// telling them otherwise
+pub async fn check_senate_member(
+    ctx: &Context,
+    command: &CommandInteraction,
+) -> Result<bool, serenity::Error> {
+    let content = match &command.member {
+        Some(_) if is_senate_member(command) => return Ok(true),
+        Some(_) => "You're not an expansion senate member !",
+        None => "You must be in a server to run this command !",
+    };
+    command
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await?;
+    Ok(false)
+}
@@ -0,0 +1,164 @@
+use std::fmt;
+
+use chrono::Utc;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{ActiveValue, DatabaseConnection, EntityTrait};
+use serenity::all::{Context, CreateEmbed, CreateMessage};
+
+use crate::CONFIG;
+use crate::entities::{bot_state, prelude::*};
+
+/// Key of the last phase announced in the feed in the bot state
+const ANNOUNCED_PHASE_KEY: &str = "announced_phase";
+
+/// The stages the contest goes through, from the configured timestamps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContestPhase {
+    Upcoming,
+    Active,
+    /// Items created during the contest can still be submitted
+    SubmissionGrace,
+    /// Submissions are closed while the last ones are reviewed
+    Review,
+    Finalized,
+}
+
+impl ContestPhase {
+    pub fn at(timestamp: i64) -> Self {
+        if timestamp < CONFIG.contest_start_timestamp {
+            ContestPhase::Upcoming
+        } else if timestamp < CONFIG.contest_end_timestamp {
+            ContestPhase::Active
+        } else if timestamp < CONFIG.submission_grace_end_timestamp {
+            ContestPhase::SubmissionGrace
+        } else if timestamp < CONFIG.review_end_timestamp {
+            ContestPhase::Review
+        } else {
+            ContestPhase::Finalized
+        }
+    }
+
+    pub fn current() -> Self {
+        Self::at(Utc::now().timestamp())
+    }
+
+    /// Identifier of the phase in the bot state
+    fn key(&self) -> &'static str {
+        match self {
+            ContestPhase::Upcoming => "upcoming",
+            ContestPhase::Active => "active",
+            ContestPhase::SubmissionGrace => "grace",
+            ContestPhase::Review => "review",
+            ContestPhase::Finalized => "finalized",
+        }
+    }
+
+    /// Commands that can't be run during this phase
+    fn closed_commands(&self) -> &'static [&'static str] {
+        match self {
+            ContestPhase::Upcoming => &["submit"],
+            ContestPhase::Active | ContestPhase::SubmissionGrace => &[],
+            ContestPhase::Review => &["submit", "join", "team"],
+            ContestPhase::Finalized => &["submit", "join", "team", "verify"],
+        }
+    }
+
+    pub fn allows_command(&self, name: &str) -> bool {
+        !self.closed_commands().contains(&name)
+    }
+
+    /// Message posted in the feed when the contest enters this phase
+    fn announcement(&self) -> String {
+        match self {
+            ContestPhase::Upcoming => format!(
+                "### ⏳ The contest starts <t:{}:R> !",
+                CONFIG.contest_start_timestamp
+            ),
+            ContestPhase::Active => format!(
+                "### 🐛 The contest has started ! Submissions are open until <t:{}:f>",
+                CONFIG.contest_end_timestamp
+            ),
+            ContestPhase::SubmissionGrace => format!(
+                "### ⌛ The contest has ended ! Issues, comments and PRs created before the end can still be submitted until <t:{}:f>",
+                CONFIG.submission_grace_end_timestamp
+            ),
+            ContestPhase::Review => format!(
+                "### 🔍 Submissions are closed ! The last ones are being reviewed, results will be final <t:{}:R>",
+                CONFIG.review_end_timestamp
+            ),
+            ContestPhase::Finalized => {
+                "### 🏁 The review is over, the results are final ! Thanks for participating"
+                    .to_string()
+            }
+        }
+    }
+}
+
+impl fmt::Display for ContestPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ContestPhase::Upcoming => "Upcoming",
+                ContestPhase::Active => "Active",
+                ContestPhase::SubmissionGrace => "Submission Grace",
+                ContestPhase::Review => "Review",
+                ContestPhase::Finalized => "Finalized",
+            }
+        )
+    }
+}
+
+/// Announces the current phase in the feed if it changed since the last announcement,
+/// returning the phase the contest entered. The first phase seen is only recorded
+pub async fn announce_phase_transition(
+    db_conn: &DatabaseConnection,
+    ctx: &Context,
+) -> Option<ContestPhase> {
+    let phase = ContestPhase::current();
+    let announced = match BotState::find_by_id(ANNOUNCED_PHASE_KEY).one(db_conn).await {
+        Ok(state) => state.map(|state| state.value),
+        Err(e) => {
+            log::error!("Error while fetching announced contest phase: {e:?}");
+            return None;
+        }
+    };
+    if announced.as_deref() == Some(phase.key()) {
+        return None;
+    }
+    let first_run = announced.is_none();
+
+    // Saved before announcing, so that a failing announcement isn't repeated every loop
+    let state = bot_state::ActiveModel {
+        key: ActiveValue::Set(ANNOUNCED_PHASE_KEY.to_string()),
+        value: ActiveValue::Set(phase.key().to_string()),
+    };
+    if let Err(e) = BotState::insert(state)
+        .on_conflict(
+            OnConflict::column(bot_state::Column::Key)
+                .update_column(bot_state::Column::Value)
+                .to_owned(),
+        )
+        .exec(db_conn)
+        .await
+    {
+        log::error!("Error while saving announced contest phase: {e:?}");
+        return None;
+    }
+    // The bot may be started long after the phase began, which isn't worth announcing
+    if first_run {
+        return None;
+    }
+    if let Err(e) = CONFIG
+        .feed_channel
+        .send_message(
+            &ctx.http,
+            CreateMessage::new().embed(CreateEmbed::new().description(phase.announcement())),
+        )
+        .await
+    {
+        log::error!("Error while announcing contest phase {phase}: {e:?}");
+    }
+    Some(phase)
+}
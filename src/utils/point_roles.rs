@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use sea_orm::DatabaseConnection;
+use serenity::all::{Context, GuildId, Member, UserId};
+
+use crate::CONFIG;
+use crate::utils::ui::{Score, compute_scores};
+
+/// Number of members fetched per request when reconciling every member
+const MEMBERS_PAGE_SIZE: u64 = 1000;
+
+/// Gives users the point roles of the thresholds their confirmed points reached,
+/// and takes back the roles of the thresholds they fell below
+pub async fn sync_point_roles(
+    db_conn: &DatabaseConnection,
+    ctx: &Context,
+    guild_id: Option<GuildId>,
+    user_ids: &[String],
+) {
+    let Some(guild_id) = guild_id else {
+        return;
+    };
+    if CONFIG.point_roles.is_empty() {
+        return;
+    }
+    let score_map = match compute_scores(db_conn).await {
+        Ok(score_map) => score_map,
+        Err(e) => {
+            log::error!("Error while computing scores for point roles: {e:?}");
+            return;
+        }
+    };
+    for user_id in user_ids {
+        let Ok(user) = user_id.parse::<UserId>() else {
+            continue;
+        };
+        match guild_id.member(&ctx.http, user).await {
+            Ok(member) => {
+                apply_point_roles(ctx, &member, &score_map).await;
+            }
+            Err(e) => log::error!("Error while fetching member {user_id}: {e:?}"),
+        }
+    }
+}
+
+/// Applies the point roles to every member of the server, fixing the roles that drifted from
+/// the scores. Returns the number of roles added and removed
+pub async fn reconcile_point_roles(
+    db_conn: &DatabaseConnection,
+    ctx: &Context,
+    guild_id: GuildId,
+) -> Result<(usize, usize), serenity::Error> {
+    let score_map = compute_scores(db_conn).await.map_err(|e| {
+        log::error!("Error while computing scores for point roles: {e:?}");
+        serenity::Error::Other("Error while computing scores")
+    })?;
+    let (mut added, mut removed) = (0, 0);
+    let mut after = None;
+    loop {
+        let members = guild_id
+            .members(&ctx.http, Some(MEMBERS_PAGE_SIZE), after)
+            .await?;
+        for member in &members {
+            let (a, r) = apply_point_roles(ctx, member, &score_map).await;
+            added += a;
+            removed += r;
+        }
+        match members.last() {
+            Some(last) if members.len() as u64 == MEMBERS_PAGE_SIZE => after = Some(last.user.id),
+            _ => break,
+        }
+    }
+    Ok((added, removed))
+}
+
+/// Adds and removes the point roles of a member, returning the number of roles added and removed
+async fn apply_point_roles(
+    ctx: &Context,
+    member: &Member,
+    score_map: &HashMap<String, Score>,
+) -> (usize, usize) {
+    if member.user.bot {
+        return (0, 0);
+    }
+    let points = score_map
+        .get(&member.user.id.get().to_string())
+        .map_or(0, |score| score.get_confirmed_points());
+    let (mut added, mut removed) = (0, 0);
+    for point_role in &CONFIG.point_roles {
+        let has_role = member.roles.contains(&point_role.role);
+        let (guild_id, user_id, role_id) = (member.guild_id, member.user.id, point_role.role);
+        if points >= point_role.points && !has_role {
+            match ctx
+                .http
+                .add_member_role(guild_id, user_id, role_id, Some("Point threshold reached"))
+                .await
+            {
+                Ok(()) => added += 1,
+                Err(e) => {
+                    log::error!("Error while giving point role {role_id} to {user_id}: {e:?}")
+                }
+            }
+        } else if points < point_role.points && has_role {
+            match ctx
+                .http
+                .remove_member_role(guild_id, user_id, role_id, Some("Below point threshold"))
+                .await
+            {
+                Ok(()) => removed += 1,
+                Err(e) => {
+                    log::error!("Error while removing point role {role_id} from {user_id}: {e:?}")
+                }
+            }
+        }
+    }
+    (added, removed)
+}
@@ -0,0 +1,256 @@
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+
+use crate::CONFIG;
+use crate::entities::actions::{self, ActionType};
+use crate::entities::{multiplier_events, prelude::*};
+use crate::utils::config::{DifficultyTier, LabelBonus};
+use crate::utils::issues::{GithubTarget, REPO_NAME, REPO_OWNER};
+
+/// The points of a submission and how they were obtained
+#[derive(Debug, Clone)]
+pub struct Points {
+    pub base: i64,
+    pub multiplier: f64,
+    pub bonus: i64,
+    /// Factor of the multiplier event the submission was created during
+    pub event_factor: f64,
+    pub event_id: Option<u32>,
+    /// Human readable adjustments, such as `severity: critical x2`
+    pub details: Vec<String>,
+}
+
+impl Points {
+    pub fn new(action_type: ActionType) -> Self {
+        Self {
+            base: action_type.get_points(),
+            multiplier: 1.0,
+            bonus: 0,
+            event_factor: 1.0,
+            event_id: None,
+            details: vec![],
+        }
+    }
+
+    /// Replaces the base points by those of the difficulty tier picked by the reviewer
+    pub fn with_tier(mut self, tier: Option<&DifficultyTier>) -> Self {
+        if let Some(tier) = tier {
+            self.base = tier.points;
+            self.details.push(format!("{} difficulty", tier.name));
+        }
+        self
+    }
+
+    /// Applies the configured label bonuses for the labels of the submitted issue
+    pub fn with_labels(self, labels: &[String]) -> Self {
+        self.with_label_bonuses(labels, &CONFIG.label_bonuses)
+    }
+
+    fn with_label_bonuses(mut self, labels: &[String], label_bonuses: &[LabelBonus]) -> Self {
+        for label_bonus in label_bonuses {
+            if !labels.contains(&label_bonus.label) {
+                continue;
+            }
+            if label_bonus.multiplier != 1.0 {
+                self.multiplier *= label_bonus.multiplier;
+                self.details
+                    .push(format!("{} x{}", label_bonus.label, label_bonus.multiplier));
+            }
+            if label_bonus.bonus != 0 {
+                self.bonus += label_bonus.bonus;
+                self.details
+                    .push(format!("{} {:+}", label_bonus.label, label_bonus.bonus));
+            }
+        }
+        self
+    }
+
+    /// Applies the multiplier event the submission was created during
+    pub fn with_event(mut self, event: Option<&multiplier_events::Model>) -> Self {
+        if let Some(event) = event {
+            self.event_factor = event.factor;
+            self.event_id = Some(event.id);
+            self.details
+                .push(format!("{} x{}", event.name, event.factor));
+        }
+        self
+    }
+
+    pub fn total(&self) -> i64 {
+        (self.base as f64 * self.multiplier * self.event_factor).round() as i64 + self.bonus
+    }
+
+    /// Points gained from the multiplier event
+    pub fn event_points(&self) -> i64 {
+        self.total() - ((self.base as f64 * self.multiplier).round() as i64 + self.bonus)
+    }
+}
+
+impl std::fmt::Display for Points {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:+} points", self.total())?;
+        if !self.details.is_empty() {
+            write!(f, " ({})", self.details.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Finds the multiplier event with the highest factor for a submission created on GitHub at `timestamp`
+pub async fn find_multiplier_event(
+    db_conn: &DatabaseConnection,
+    action_type: ActionType,
+    timestamp: i64,
+) -> Option<multiplier_events::Model> {
+    let events = MultiplierEvents::find()
+        .filter(multiplier_events::Column::StartsAt.lte(timestamp))
+        .filter(multiplier_events::Column::EndsAt.gt(timestamp))
+        .filter(multiplier_events::Column::CancelledAt.is_null())
+        .all(db_conn)
+        .await;
+    match events {
+        Ok(events) => events
+            .into_iter()
+            .filter(|event| event.applies_to(action_type, timestamp))
+            .max_by(|a, b| a.factor.total_cmp(&b.factor)),
+        Err(e) => {
+            log::error!("Error while fetching multiplier events: {e:?}");
+            None
+        }
+    }
+}
+
+/// Computes the points of a submission being confirmed, from its difficulty tier, the current
+/// labels of its issue, or of the fixed issue for a bugfix PR, and the multiplier event it was
+/// created during
+pub async fn compute_confirmed_points(
+    db_conn: &DatabaseConnection,
+    action: &actions::Model,
+    tier: Option<&DifficultyTier>,
+) -> octocrab::Result<Points> {
+    let event = match action.github_created_at {
+        Some(timestamp) => find_multiplier_event(db_conn, action.action_type, timestamp).await,
+        None => None,
+    };
+    let mut points = Points::new(action.action_type)
+        .with_tier(tier)
+        .with_event(event.as_ref());
+    let issue_number = match (
+        action.linked_issue,
+        GithubTarget::from_url(&action.github_link),
+    ) {
+        (Some(linked), _) => Some(linked),
+        (None, Some(target)) => Some(target.issue_number()),
+        (None, None) => None,
+    };
+    if let Some(number) = issue_number {
+        let issue = octocrab::instance()
+            .issues(REPO_OWNER, REPO_NAME)
+            .get(number)
+            .await?;
+        let labels: Vec<String> = issue.labels.into_iter().map(|label| label.name).collect();
+        points = points.with_labels(&labels);
+    }
+    Ok(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn bonus(label: &str, multiplier: f64, bonus: i64) -> LabelBonus {
+        LabelBonus {
+            label: label.to_string(),
+            multiplier,
+            bonus,
+        }
+    }
+
+    #[test]
+    fn stacks_label_multipliers_and_bonuses() {
+        let bonuses = [
+            bonus("severity: critical", 2.0, 0),
+            bonus("regression", 1.5, 0),
+            bonus("crash", 1.0, 1),
+            bonus("save", 1.0, 2),
+        ];
+        // 5 x2 x1.5 + 1 + 2
+        let points = Points::new(ActionType::PRFix).with_label_bonuses(
+            &labels(&["crash", "severity: critical", "regression", "save"]),
+            &bonuses,
+        );
+        assert_eq!(points.total(), 18);
+        assert_eq!(points.details.len(), 4);
+
+        let points = Points::new(ActionType::PRFix).with_label_bonuses(&labels(&["bug"]), &bonuses);
+        assert_eq!(points.total(), 5);
+        assert!(points.details.is_empty());
+    }
+
+    #[test]
+    fn rounds_multiplied_points_before_bonuses() {
+        let bonuses = [bonus("regression", 1.5, 0), bonus("crash", 1.0, 1)];
+        // 1 x1.5 rounds to 2, then +1
+        let points = Points::new(ActionType::ConfirmBug)
+            .with_label_bonuses(&labels(&["regression", "crash"]), &bonuses);
+        assert_eq!(points.total(), 3);
+    }
+
+    #[test]
+    fn difficulty_tiers_override_base_points() {
+        let tier = DifficultyTier {
+            name: "Hard".to_string(),
+            emoji: '🔥',
+            points: 10,
+        };
+        let points = Points::new(ActionType::PRFix).with_tier(Some(&tier));
+        assert_eq!(points.base, 10);
+        assert_eq!(points.total(), 10);
+        assert_eq!(points.details, vec!["Hard difficulty".to_string()]);
+
+        // Label multipliers still apply to the points of the tier
+        let points = Points::new(ActionType::PRFix)
+            .with_tier(Some(&tier))
+            .with_label_bonuses(&labels(&["crash"]), &[bonus("crash", 2.0, 1)]);
+        assert_eq!(points.total(), 21);
+
+        assert_eq!(Points::new(ActionType::PRFix).with_tier(None).total(), 5);
+    }
+
+    fn event(factor: f64) -> multiplier_events::Model {
+        multiplier_events::Model {
+            id: 3,
+            name: "Double points weekend".to_string(),
+            starts_at: 0,
+            ends_at: 100,
+            factor,
+            action_type: None,
+            created_by: String::new(),
+            created_at: 0,
+            cancelled_by: None,
+            cancelled_at: None,
+        }
+    }
+
+    #[test]
+    fn applies_event_factors() {
+        let points = Points::new(ActionType::PRFix).with_event(Some(&event(2.0)));
+        assert_eq!(points.total(), 10);
+        assert_eq!(points.event_points(), 5);
+        assert_eq!(points.event_id, Some(3));
+
+        // Bonuses aren't multiplied by the event
+        let points = Points::new(ActionType::PRFix)
+            .with_label_bonuses(&labels(&["crash"]), &[bonus("crash", 1.0, 1)])
+            .with_event(Some(&event(1.5)));
+        assert_eq!(points.total(), 9);
+        assert_eq!(points.event_points(), 3);
+
+        let points = Points::new(ActionType::PRFix).with_event(None);
+        assert_eq!(points.event_points(), 0);
+        assert_eq!(points.event_id, None);
+    }
+}
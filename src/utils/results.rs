@@ -0,0 +1,224 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use sea_orm::{ActiveValue, DatabaseConnection, EntityTrait};
+use serenity::all::{Context, CreateEmbed, CreateMessage, GuildId, UserId};
+
+use crate::CONFIG;
+use crate::entities::{final_standings, prelude::*};
+use crate::utils::contestants::{load_display_names, user_label};
+use crate::utils::phase::ContestPhase;
+use crate::utils::teams::compute_team_scores;
+use crate::utils::ui::{
+    CATEGORIES, category_title, category_unit, compute_scores, escape_markdown, rank_users,
+    tied_ranks,
+};
+
+/// Finalizes the results once the review is over, unless they already were. Called on every
+/// iteration of the main loop, so that a failure is retried and the results are still frozen
+/// when the bot first starts after the end of the review
+pub async fn finalize_results_if_due(db_conn: &DatabaseConnection, ctx: &Context) {
+    if ContestPhase::current() != ContestPhase::Finalized {
+        return;
+    }
+    match FinalStandings::find().one(db_conn).await {
+        Ok(None) => (),
+        Ok(Some(_)) => return,
+        Err(e) => {
+            log::error!("Error while fetching final standings: {e:?}");
+            return;
+        }
+    }
+    // Results without anyone ranked would otherwise be announced again on every call
+    match compute_scores(db_conn).await {
+        Ok(score_map) if rank_users(&score_map, None).is_empty() => return,
+        Ok(_) => (),
+        Err(e) => {
+            log::error!("Error while computing scores: {e:?}");
+            return;
+        }
+    }
+    if let Err(e) = finalize_results(db_conn, ctx).await {
+        log::error!("{e}");
+    }
+}
+
+/// Freezes the standings, announces the winners in the feed and gives them their roles.
+/// Running it again replaces the previous results
+pub async fn finalize_results(db_conn: &DatabaseConnection, ctx: &Context) -> Result<(), String> {
+    let score_map = compute_scores(db_conn)
+        .await
+        .map_err(|e| format!("Error while computing scores: {e:?}"))?;
+    let previous = FinalStandings::find()
+        .all(db_conn)
+        .await
+        .map_err(|e| format!("Error while fetching previous standings: {e:?}"))?;
+
+    let now = Utc::now().timestamp();
+    let mut standings = vec![];
+    for action_type in CATEGORIES {
+        for (user_id, rank, value) in rank_users(&score_map, action_type) {
+            standings.push(final_standings::Model {
+                id: 0,
+                action_type,
+                rank,
+                user_id,
+                value,
+                finalized_at: now,
+            });
+        }
+    }
+
+    FinalStandings::delete_many()
+        .exec(db_conn)
+        .await
+        .map_err(|e| format!("Error while clearing previous standings: {e:?}"))?;
+    if !standings.is_empty() {
+        FinalStandings::insert_many(standings.iter().map(|standing| {
+            final_standings::ActiveModel {
+                id: ActiveValue::NotSet,
+                action_type: ActiveValue::Set(standing.action_type),
+                rank: ActiveValue::Set(standing.rank),
+                user_id: ActiveValue::Set(standing.user_id.clone()),
+                value: ActiveValue::Set(standing.value),
+                finalized_at: ActiveValue::Set(now),
+            }
+        }))
+        .exec(db_conn)
+        .await
+        .map_err(|e| format!("Error while saving standings: {e:?}"))?;
+    }
+
+    let display_names = load_display_names(db_conn).await.unwrap_or_else(|e| {
+        log::error!("Error while fetching contestant display names: {e:?}");
+        HashMap::new()
+    });
+    let mut description = String::new();
+    for action_type in CATEGORIES {
+        description.push_str(&format!("\n\n**{}**", category_title(action_type)));
+        let winners: Vec<_> = standings
+            .iter()
+            .filter(|s| s.action_type == action_type && s.rank <= 3)
+            .collect();
+        if winners.is_empty() {
+            description.push_str("\nNo winner");
+        }
+        for standing in winners {
+            description.push_str(&format!(
+                "\n{} {}: {} {}",
+                match standing.rank {
+                    1 => "🥇",
+                    2 => "🥈",
+                    _ => "🥉",
+                },
+                user_label(&display_names, &standing.user_id),
+                standing.value,
+                category_unit(action_type),
+            ));
+        }
+    }
+    match compute_team_scores(db_conn, &score_map).await {
+        Ok(team_scores) => {
+            // Teams tied for the first place all win
+            let ranks = tied_ranks(&team_scores.iter().map(|t| t.points).collect::<Vec<_>>());
+            let winners: Vec<_> = team_scores
+                .iter()
+                .zip(ranks)
+                .take_while(|(_, rank)| *rank == 1)
+                .map(|(best, _)| {
+                    format!(
+                        "\n🏆 **{}**: {} points",
+                        escape_markdown(&best.team.name),
+                        best.points
+                    )
+                })
+                .collect();
+            if !winners.is_empty() {
+                description.push_str(&format!("\n\n**Teams**{}", winners.concat()));
+            }
+        }
+        Err(e) => log::error!("Error while computing team scores: {e:?}"),
+    }
+    CONFIG
+        .feed_channel
+        .send_message(
+            &ctx.http,
+            CreateMessage::new().embed(
+                CreateEmbed::new()
+                    .title("🏁 Bug Catching Contest 2025 Results")
+                    .description(description.trim_start()),
+            ),
+        )
+        .await
+        .map_err(|e| format!("Error while announcing results: {e:?}"))?;
+
+    assign_winner_roles(ctx, &previous, &standings).await;
+    Ok(())
+}
+
+/// Gives the configured roles to the new winners, and takes them back from previous winners
+/// who lost their place after a correction
+async fn assign_winner_roles(
+    ctx: &Context,
+    previous: &[final_standings::Model],
+    standings: &[final_standings::Model],
+) {
+    if CONFIG.winner_roles.is_empty() {
+        return;
+    }
+    let guild_id: GuildId = match CONFIG.feed_channel.to_channel(&ctx.http).await {
+        Ok(channel) => match channel.guild() {
+            Some(channel) => channel.guild_id,
+            None => {
+                log::error!("The feed channel isn't in a server, can't assign winner roles");
+                return;
+            }
+        },
+        Err(e) => {
+            log::error!("Error while fetching feed channel: {e:?}");
+            return;
+        }
+    };
+
+    for winner_role in &CONFIG.winner_roles {
+        let winners = |standings: &[final_standings::Model]| -> HashSet<String> {
+            standings
+                .iter()
+                .filter(|s| s.action_type == winner_role.action_type && s.rank <= winner_role.top)
+                .map(|s| s.user_id.clone())
+                .collect()
+        };
+        let previous_winners = winners(previous);
+        let new_winners = winners(standings);
+
+        for user_id in previous_winners.difference(&new_winners) {
+            let Ok(user_id) = user_id.parse::<UserId>() else {
+                continue;
+            };
+            if let Err(e) = ctx
+                .http
+                .remove_member_role(
+                    guild_id,
+                    user_id,
+                    winner_role.role,
+                    Some("Contest results corrected"),
+                )
+                .await
+            {
+                log::error!("Error while removing winner role from {user_id}: {e:?}");
+            }
+        }
+        for user_id in &new_winners {
+            let Ok(user_id) = user_id.parse::<UserId>() else {
+                continue;
+            };
+            if let Err(e) = ctx
+                .http
+                .add_member_role(guild_id, user_id, winner_role.role, Some("Contest winner"))
+                .await
+            {
+                log::error!("Error while giving winner role to {user_id}: {e:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use sea_orm::{
+    ActiveValue, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+};
+
+use crate::CONFIG;
+use crate::entities::actions::ActionType;
+use crate::entities::{leaderboard_snapshots, prelude::*};
+use crate::utils::phase::ContestPhase;
+use crate::utils::ui::{CATEGORIES, Score, compute_scores, rank_users};
+
+/// Saves the current ranks of every user when the last snapshot is older than the configured interval
+pub async fn take_snapshot_if_due(db_conn: &DatabaseConnection) {
+    // Nothing to rank before the contest, and the results are frozen once it is finalized
+    if matches!(
+        ContestPhase::current(),
+        ContestPhase::Upcoming | ContestPhase::Finalized
+    ) {
+        return;
+    }
+    let now = Utc::now().timestamp();
+    let last = LeaderboardSnapshots::find()
+        .order_by_desc(leaderboard_snapshots::Column::TakenAt)
+        .one(db_conn)
+        .await;
+    match last {
+        Ok(Some(last)) if now - last.taken_at < CONFIG.snapshot_interval => return,
+        Ok(_) => (),
+        Err(e) => {
+            log::error!("Error while fetching last leaderboard snapshot: {e:?}");
+            return;
+        }
+    }
+
+    let score_map = match compute_scores(db_conn).await {
+        Ok(score_map) => score_map,
+        Err(e) => {
+            log::error!("Error while computing scores for snapshot: {e:?}");
+            return;
+        }
+    };
+    let rows: Vec<_> =
+        CATEGORIES
+            .into_iter()
+            .flat_map(|action_type| {
+                rank_users(&score_map, action_type).into_iter().map(
+                    move |(user_id, rank, value)| leaderboard_snapshots::ActiveModel {
+                        id: ActiveValue::NotSet,
+                        taken_at: ActiveValue::Set(now),
+                        action_type: ActiveValue::Set(action_type),
+                        rank: ActiveValue::Set(rank),
+                        user_id: ActiveValue::Set(user_id),
+                        value: ActiveValue::Set(value),
+                    },
+                )
+            })
+            .collect();
+    if rows.is_empty() {
+        return;
+    }
+    if let Err(e) = LeaderboardSnapshots::insert_many(rows).exec(db_conn).await {
+        log::error!("Error while saving leaderboard snapshot: {e:?}");
+    }
+}
+
+/// The latest snapshot taken at or before `timestamp`, empty if there is none
+pub async fn load_snapshot(
+    db_conn: &DatabaseConnection,
+    timestamp: i64,
+) -> Result<Vec<leaderboard_snapshots::Model>, DbErr> {
+    let Some(latest) = LeaderboardSnapshots::find()
+        .filter(leaderboard_snapshots::Column::TakenAt.lte(timestamp))
+        .order_by_desc(leaderboard_snapshots::Column::TakenAt)
+        .one(db_conn)
+        .await?
+    else {
+        return Ok(vec![]);
+    };
+    LeaderboardSnapshots::find()
+        .filter(leaderboard_snapshots::Column::TakenAt.eq(latest.taken_at))
+        .order_by_asc(leaderboard_snapshots::Column::Rank)
+        .all(db_conn)
+        .await
+}
+
+/// Ranks of every user in each category, now and at the start of the movement period
+#[derive(Default)]
+pub struct RankMovements {
+    current: HashMap<Option<ActionType>, HashMap<String, u32>>,
+    /// Unset when no snapshot is old enough to compare to
+    previous: Option<HashMap<Option<ActionType>, HashMap<String, u32>>>,
+}
+
+impl RankMovements {
+    pub async fn load(
+        db_conn: &DatabaseConnection,
+        score_map: &HashMap<String, Score>,
+    ) -> Result<Self, DbErr> {
+        let current = CATEGORIES
+            .into_iter()
+            .map(|action_type| {
+                let ranks = rank_users(score_map, action_type)
+                    .into_iter()
+                    .map(|(user_id, rank, _)| (user_id, rank))
+                    .collect();
+                (action_type, ranks)
+            })
+            .collect();
+
+        let snapshot = load_snapshot(
+            db_conn,
+            Utc::now().timestamp() - CONFIG.rank_movement_period,
+        )
+        .await?;
+        let previous = if snapshot.is_empty() {
+            None
+        } else {
+            let mut previous: HashMap<Option<ActionType>, HashMap<String, u32>> = HashMap::new();
+            for row in snapshot {
+                previous
+                    .entry(row.action_type)
+                    .or_default()
+                    .insert(row.user_id, row.rank);
+            }
+            Some(previous)
+        };
+        Ok(RankMovements { current, previous })
+    }
+
+    /// How a user moved in a category, e.g. ` ▲2`, ` ▼1` or ` NEW`, empty when unranked or unchanged
+    pub fn marker(&self, action_type: Option<ActionType>, user_id: &str) -> String {
+        let (Some(previous), Some(current)) = (
+            &self.previous,
+            self.current
+                .get(&action_type)
+                .and_then(|ranks| ranks.get(user_id)),
+        ) else {
+            return String::new();
+        };
+        match previous
+            .get(&action_type)
+            .and_then(|ranks| ranks.get(user_id))
+        {
+            None => " NEW".to_string(),
+            Some(previous) if previous > current => format!(" ▲{}", previous - current),
+            Some(previous) if previous < current => format!(" ▼{}", current - previous),
+            Some(_) => String::new(),
+        }
+    }
+}
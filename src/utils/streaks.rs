@@ -0,0 +1,332 @@
+use std::collections::{BTreeSet, HashMap};
+
+use chrono::Utc;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{ActiveValue, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter};
+use serenity::all::{Context, CreateEmbed, CreateMessage};
+
+use crate::CONFIG;
+use crate::entities::actions::ActionStatus;
+use crate::entities::{actions, bot_state, contestants, prelude::*};
+use crate::utils::participants::load_participants;
+
+const DAY: i64 = 24 * 60 * 60;
+
+/// Consecutive days and weeks (UTC, from Monday) in which a user created confirmed items
+#[derive(Default, Clone, Copy)]
+pub struct Streaks {
+    pub current_daily: u32,
+    pub longest_daily: u32,
+    pub current_weekly: u32,
+    pub longest_weekly: u32,
+}
+
+/// Kinds of streaks, each with their own milestones
+#[derive(Clone, Copy)]
+enum StreakKind {
+    Daily,
+    Weekly,
+}
+
+impl StreakKind {
+    /// Index of the period containing `timestamp`
+    fn period(&self, timestamp: i64) -> i64 {
+        let day = timestamp.div_euclid(DAY);
+        match self {
+            StreakKind::Daily => day,
+            // The unix epoch was a Thursday
+            StreakKind::Weekly => (day + 3).div_euclid(7),
+        }
+    }
+
+    fn unit(&self) -> &'static str {
+        match self {
+            StreakKind::Daily => "day",
+            StreakKind::Weekly => "week",
+        }
+    }
+
+    fn milestones(&self) -> &'static [u32] {
+        match self {
+            StreakKind::Daily => &CONFIG.daily_streak_milestones,
+            StreakKind::Weekly => &CONFIG.weekly_streak_milestones,
+        }
+    }
+
+    /// Indexes of the periods containing the given days
+    fn periods(&self, days: &BTreeSet<i64>) -> BTreeSet<i64> {
+        days.iter().map(|day| self.period(day * DAY)).collect()
+    }
+
+    /// Key of the last milestone announced for a user in the bot state
+    fn milestone_key(&self, user_id: &str) -> String {
+        match self {
+            StreakKind::Daily => format!("daily_streak_milestone-{user_id}"),
+            StreakKind::Weekly => format!("weekly_streak_milestone-{user_id}"),
+        }
+    }
+}
+
+/// The current and longest runs of consecutive periods. The current run still counts when the
+/// last period was the one before `now_period`, as the user can extend it until it ends
+fn runs(periods: &BTreeSet<i64>, now_period: i64) -> (u32, u32) {
+    let (mut longest, mut run, mut last) = (0, 0, None);
+    for &period in periods {
+        run = if last == Some(period - 1) { run + 1 } else { 1 };
+        longest = longest.max(run);
+        last = Some(period);
+    }
+    let current = match last {
+        Some(last) if last >= now_period - 1 => run,
+        _ => 0,
+    };
+    (current, longest)
+}
+
+/// The first period and the length of the latest run of consecutive periods
+fn last_run(periods: &BTreeSet<i64>) -> Option<(i64, u32)> {
+    let mut run: Option<(i64, u32)> = None;
+    let mut last = None;
+    for &period in periods {
+        run = match run {
+            Some((start, length)) if last == Some(period - 1) => Some((start, length + 1)),
+            _ => Some((period, 1)),
+        };
+        last = Some(period);
+    }
+    run
+}
+
+/// The first and last periods of the streak a milestone was announced for, and the milestone,
+/// stored as `first:last:milestone`. Older values only stored `first:milestone`
+fn parse_announced(value: &str) -> Option<(i64, i64, u32)> {
+    let parts: Vec<&str> = value.split(':').collect();
+    let (start, end, milestone) = match parts[..] {
+        [start, milestone] => (start, start, milestone),
+        [start, end, milestone] => (start, end, milestone),
+        _ => return None,
+    };
+    Some((
+        start.parse().ok()?,
+        end.parse().ok()?,
+        milestone.parse().ok()?,
+    ))
+}
+
+/// Whether `milestone`, reached in the run spanning the `run` periods, still has to be announced.
+/// The announced streak is the same as `run` when it falls inside it, as late reviews can extend
+/// a streak on both ends
+fn is_new_milestone(announced: Option<(i64, i64, u32)>, run: (i64, i64), milestone: u32) -> bool {
+    match announced {
+        Some((start, end, announced)) if run.0 <= start && end <= run.1 => announced < milestone,
+        _ => true,
+    }
+}
+
+/// The days in which each user created confirmed items. Shared submissions count for every
+/// participant, excluded contestants are left out
+async fn load_active_days(
+    db_conn: &DatabaseConnection,
+) -> Result<HashMap<String, BTreeSet<i64>>, DbErr> {
+    let participants = load_participants(db_conn).await?;
+    let mut days: HashMap<String, BTreeSet<i64>> = HashMap::new();
+    for action in Actions::find()
+        .filter(actions::Column::ActionStatus.eq(ActionStatus::Confirmed))
+        .all(db_conn)
+        .await?
+    {
+        let Some(created_at) = action.github_created_at else {
+            continue;
+        };
+        let user_ids = match participants.get(&action.id) {
+            Some(participants) => participants.iter().map(|p| p.user_id.clone()).collect(),
+            None => vec![action.user_id],
+        };
+        for user_id in user_ids {
+            days.entry(user_id)
+                .or_default()
+                .insert(StreakKind::Daily.period(created_at));
+        }
+    }
+    for contestant in Contestants::find()
+        .filter(contestants::Column::Excluded.eq(true))
+        .all(db_conn)
+        .await?
+    {
+        days.remove(&contestant.user_id);
+    }
+    Ok(days)
+}
+
+/// Computes the streaks of every user from the GitHub creation dates of their confirmed
+/// submissions. Shared submissions count for every participant, excluded contestants are left out
+pub async fn compute_streaks(
+    db_conn: &DatabaseConnection,
+) -> Result<HashMap<String, Streaks>, DbErr> {
+    let days = load_active_days(db_conn).await?;
+    // Streaks can't go on once the contest is over
+    let now = Utc::now().timestamp().min(CONFIG.contest_end_timestamp);
+    Ok(days
+        .into_iter()
+        .map(|(user_id, days)| {
+            let weeks = StreakKind::Weekly.periods(&days);
+            let (current_daily, longest_daily) = runs(&days, StreakKind::Daily.period(now));
+            let (current_weekly, longest_weekly) = runs(&weeks, StreakKind::Weekly.period(now));
+            let streaks = Streaks {
+                current_daily,
+                longest_daily,
+                current_weekly,
+                longest_weekly,
+            };
+            (user_id, streaks)
+        })
+        .collect())
+}
+
+/// Announces in the feed the streak milestones the users reached in their latest streaks.
+/// Reviews can come after a streak ended, so the length it reached counts rather than whether
+/// it still goes on, and a milestone is announced again when reached in a new streak
+pub async fn announce_streak_milestones(
+    db_conn: &DatabaseConnection,
+    ctx: &Context,
+    user_ids: &[String],
+) {
+    if CONFIG.daily_streak_milestones.is_empty() && CONFIG.weekly_streak_milestones.is_empty() {
+        return;
+    }
+    let active_days = match load_active_days(db_conn).await {
+        Ok(active_days) => active_days,
+        Err(e) => {
+            log::error!("Error while computing streaks: {e:?}");
+            return;
+        }
+    };
+    for user_id in user_ids {
+        let Some(days) = active_days.get(user_id) else {
+            continue;
+        };
+        for kind in [StreakKind::Daily, StreakKind::Weekly] {
+            let Some((run_start, length)) = last_run(&kind.periods(days)) else {
+                continue;
+            };
+            let Some(milestone) = kind.milestones().iter().filter(|m| **m <= length).max() else {
+                continue;
+            };
+            let run = (run_start, run_start + length as i64 - 1);
+            let key = kind.milestone_key(user_id);
+            let announced = match BotState::find_by_id(&key).one(db_conn).await {
+                Ok(state) => state.and_then(|state| parse_announced(&state.value)),
+                Err(e) => {
+                    log::error!("Error while fetching announced streak milestone: {e:?}");
+                    continue;
+                }
+            };
+            if !is_new_milestone(announced, run, *milestone) {
+                continue;
+            }
+
+            let state = bot_state::ActiveModel {
+                key: ActiveValue::Set(key),
+                value: ActiveValue::Set(format!("{}:{}:{milestone}", run.0, run.1)),
+            };
+            if let Err(e) = BotState::insert(state)
+                .on_conflict(
+                    OnConflict::column(bot_state::Column::Key)
+                        .update_column(bot_state::Column::Value)
+                        .to_owned(),
+                )
+                .exec(db_conn)
+                .await
+            {
+                log::error!("Error while saving announced streak milestone: {e:?}");
+                continue;
+            }
+            if let Err(e) = CONFIG
+                .feed_channel
+                .send_message(
+                    &ctx.http,
+                    CreateMessage::new().embed(CreateEmbed::new().description(format!(
+                        "### 🔥 <@{user_id}> reached a {milestone} {} streak !",
+                        kind.unit()
+                    ))),
+                )
+                .await
+            {
+                log::error!("Error while announcing streak milestone of {user_id}: {e:?}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn periods(periods: &[i64]) -> BTreeSet<i64> {
+        periods.iter().copied().collect()
+    }
+
+    #[test]
+    fn splits_days_and_weeks_from_monday() {
+        // 1970-01-01 was a Thursday
+        assert_eq!(StreakKind::Daily.period(0), 0);
+        assert_eq!(StreakKind::Daily.period(DAY - 1), 0);
+        assert_eq!(StreakKind::Daily.period(DAY), 1);
+        assert_eq!(StreakKind::Daily.period(-1), -1);
+        // Sunday 1970-01-04 ends the first week, Monday 1970-01-05 starts the next one
+        assert_eq!(StreakKind::Weekly.period(3 * DAY), 0);
+        assert_eq!(StreakKind::Weekly.period(4 * DAY - 1), 0);
+        assert_eq!(StreakKind::Weekly.period(4 * DAY), 1);
+        assert_eq!(StreakKind::Weekly.period(10 * DAY), 1);
+        assert_eq!(StreakKind::Weekly.period(11 * DAY), 2);
+        // Monday 1969-12-29 starts the week of the epoch
+        assert_eq!(StreakKind::Weekly.period(-3 * DAY), 0);
+        assert_eq!(StreakKind::Weekly.period(-3 * DAY - 1), -1);
+        assert_eq!(
+            StreakKind::Weekly.periods(&periods(&[3, 4, 5, 11])),
+            periods(&[0, 1, 2])
+        );
+    }
+
+    #[test]
+    fn counts_current_and_longest_runs() {
+        assert_eq!(runs(&periods(&[]), 10), (0, 0));
+        assert_eq!(runs(&periods(&[1, 2, 3, 7, 8]), 8), (2, 3));
+        // The current run can still be extended during the next period
+        assert_eq!(runs(&periods(&[1, 2, 3, 7, 8]), 9), (2, 3));
+        assert_eq!(runs(&periods(&[1, 2, 3, 7, 8]), 10), (0, 3));
+        assert_eq!(runs(&periods(&[5, 6, 7, 8]), 8), (4, 4));
+    }
+
+    #[test]
+    fn finds_the_latest_run() {
+        assert_eq!(last_run(&periods(&[])), None);
+        assert_eq!(last_run(&periods(&[4])), Some((4, 1)));
+        assert_eq!(last_run(&periods(&[1, 2, 3, 7, 8])), Some((7, 2)));
+        assert_eq!(last_run(&periods(&[-2, -1, 0, 1])), Some((-2, 4)));
+    }
+
+    #[test]
+    fn announces_milestones_once_per_streak() {
+        assert!(is_new_milestone(None, (10, 12), 3));
+        // Same streak, milestone already announced
+        assert!(!is_new_milestone(Some((10, 12, 3)), (10, 12), 3));
+        assert!(!is_new_milestone(Some((10, 12, 3)), (10, 13), 3));
+        // A late review adding the day before the streak doesn't make it a new one
+        assert!(!is_new_milestone(Some((10, 12, 3)), (9, 12), 3));
+        // A higher milestone of the same streak
+        assert!(is_new_milestone(Some((10, 12, 3)), (9, 15), 7));
+        // A new streak after a break
+        assert!(is_new_milestone(Some((10, 12, 3)), (15, 17), 3));
+    }
+
+    #[test]
+    fn parses_announced_milestones() {
+        assert_eq!(parse_announced("10:12:3"), Some((10, 12, 3)));
+        assert_eq!(parse_announced("-2:1:7"), Some((-2, 1, 7)));
+        // Values stored before the end of the streak was
+        assert_eq!(parse_announced("10:3"), Some((10, 10, 3)));
+        assert_eq!(parse_announced("3"), None);
+        assert_eq!(parse_announced("a:b:c"), None);
+    }
+}
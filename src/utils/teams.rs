@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait};
+
+use crate::CONFIG;
+use crate::entities::{prelude::*, teams};
+use crate::utils::ui::Score;
+
+/// The standing of a team, computed from the scores of its members
+pub struct TeamScore {
+    pub team: teams::Model,
+    pub members: Vec<String>,
+    pub points: i64,
+}
+
+/// Whether users can still leave their team or join another one at `timestamp`
+pub fn can_switch_teams(timestamp: i64) -> bool {
+    CONFIG
+        .team_switch_deadline
+        .is_none_or(|deadline| timestamp < deadline)
+}
+
+/// Computes the score of every team as the sum of its members' confirmed points, best team first
+pub async fn compute_team_scores(
+    db_conn: &DatabaseConnection,
+    score_map: &HashMap<String, Score>,
+) -> Result<Vec<TeamScore>, DbErr> {
+    let mut team_scores: HashMap<u32, TeamScore> = Teams::find()
+        .all(db_conn)
+        .await?
+        .into_iter()
+        .map(|team| {
+            (
+                team.id,
+                TeamScore {
+                    team,
+                    members: vec![],
+                    points: 0,
+                },
+            )
+        })
+        .collect();
+    for member in TeamMembers::find().all(db_conn).await? {
+        if let Some(team_score) = team_scores.get_mut(&member.team_id) {
+            team_score.points += score_map
+                .get(&member.user_id)
+                .map(|score| score.get_confirmed_points())
+                .unwrap_or(0);
+            team_score.members.push(member.user_id);
+        }
+    }
+    let mut team_scores: Vec<TeamScore> = team_scores.into_values().collect();
+    team_scores
+        .sort_by_key(|team_score| (std::cmp::Reverse(team_score.points), team_score.team.id));
+    Ok(team_scores)
+}
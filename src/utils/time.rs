@@ -0,0 +1,177 @@
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveDateTime, Utc};
+
+use crate::CONFIG;
+
+/// Parses a date typed by a user, as a unix timestamp, RFC 3339,
+/// `YYYY-MM-DD HH:MM` or `YYYY-MM-DD`, the last two being read as UTC.
+/// Dates before the start of the contest are rejected, as nothing happened then
+pub fn parse_datetime(input: &str) -> Option<DateTime<Utc>> {
+    parse_any_datetime(input).filter(|date| date.timestamp() >= CONFIG.contest_start_timestamp)
+}
+
+fn parse_any_datetime(input: &str) -> Option<DateTime<Utc>> {
+    let input = input.trim();
+    if let Ok(timestamp) = input.parse::<i64>() {
+        return DateTime::from_timestamp(timestamp, 0);
+    }
+    if let Ok(date) = DateTime::parse_from_rfc3339(input) {
+        return Some(date.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M") {
+        return Some(date.and_utc());
+    }
+    NaiveDate::parse_from_str(input, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|date| date.and_utc())
+}
+
+/// A period the leaderboards can be restricted to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeWindow {
+    All,
+    /// Since midnight UTC
+    Today,
+    /// Since monday midnight UTC
+    ThisWeek,
+    Last7Days,
+    /// From the first timestamp, included, to the second, excluded
+    Custom(i64, i64),
+}
+
+impl TimeWindow {
+    /// Start and end timestamps of the window at `now`, unset for the whole contest
+    pub fn bounds(&self, now: DateTime<Utc>) -> Option<(i64, i64)> {
+        let midnight = now.date_naive().and_hms_opt(0, 0, 0)?.and_utc();
+        match self {
+            TimeWindow::All => None,
+            TimeWindow::Today => Some((midnight.timestamp(), now.timestamp() + 1)),
+            TimeWindow::ThisWeek => Some((
+                (midnight - Days::new(now.weekday().num_days_from_monday() as u64)).timestamp(),
+                now.timestamp() + 1,
+            )),
+            TimeWindow::Last7Days => Some(((now - Days::new(7)).timestamp(), now.timestamp() + 1)),
+            TimeWindow::Custom(start, end) => Some((*start, *end)),
+        }
+    }
+
+    /// Identifier of the window in component custom ids, which can't contain `-`
+    pub fn id(&self) -> String {
+        match self {
+            TimeWindow::All => "all".to_string(),
+            TimeWindow::Today => "today".to_string(),
+            TimeWindow::ThisWeek => "week".to_string(),
+            TimeWindow::Last7Days => "7days".to_string(),
+            TimeWindow::Custom(start, end) => format!("custom_{start}_{end}"),
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "all" => Some(TimeWindow::All),
+            "today" => Some(TimeWindow::Today),
+            "week" => Some(TimeWindow::ThisWeek),
+            "7days" => Some(TimeWindow::Last7Days),
+            _ => {
+                let (start, end) = id.strip_prefix("custom_")?.split_once('_')?;
+                Some(TimeWindow::Custom(start.parse().ok()?, end.parse().ok()?))
+            }
+        }
+    }
+
+    /// Describes the window for leaderboard titles
+    pub fn label(&self) -> String {
+        match self {
+            TimeWindow::All => "Whole contest".to_string(),
+            TimeWindow::Today => "Today".to_string(),
+            TimeWindow::ThisWeek => "This week".to_string(),
+            TimeWindow::Last7Days => "Last 7 days".to_string(),
+            TimeWindow::Custom(start, end) => format!("<t:{start}:f> - <t:{end}:f>"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(input: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(input).unwrap().to_utc()
+    }
+
+    #[test]
+    fn parses_dates_in_every_format() {
+        let expected = date("2025-10-20T13:45:00Z");
+        assert_eq!(parse_datetime("1760967900"), Some(expected));
+        assert_eq!(
+            parse_datetime(" 2025-10-20T15:45:00+02:00 "),
+            Some(expected)
+        );
+        assert_eq!(parse_datetime("2025-10-20 13:45"), Some(expected));
+        assert_eq!(
+            parse_datetime("2025-10-20"),
+            Some(date("2025-10-20T00:00:00Z"))
+        );
+        assert_eq!(parse_datetime("20/10/2025"), None);
+        assert_eq!(parse_datetime("2025-10-20 25:00"), None);
+    }
+
+    #[test]
+    fn rejects_dates_before_the_contest() {
+        let start = CONFIG.contest_start_timestamp;
+        assert_eq!(
+            parse_datetime(&start.to_string()).map(|d| d.timestamp()),
+            Some(start)
+        );
+        assert_eq!(parse_datetime(&(start - 1).to_string()), None);
+        assert_eq!(parse_datetime("-5"), None);
+        assert_eq!(parse_datetime("1960-01-01"), None);
+    }
+
+    #[test]
+    fn ids_parse_back_to_the_same_window() {
+        let windows = [
+            TimeWindow::All,
+            TimeWindow::Today,
+            TimeWindow::ThisWeek,
+            TimeWindow::Last7Days,
+            TimeWindow::Custom(1760000000, 1760967900),
+        ];
+        for window in windows {
+            assert!(!window.id().contains('-'), "{window:?}");
+            assert_eq!(TimeWindow::from_id(&window.id()), Some(window));
+        }
+        assert_eq!(TimeWindow::from_id("custom_1_x"), None);
+        assert_eq!(TimeWindow::from_id("month"), None);
+    }
+
+    #[test]
+    fn computes_window_bounds() {
+        // A Wednesday
+        let now = date("2025-10-22T13:45:00Z");
+        let end = now.timestamp() + 1;
+        assert_eq!(TimeWindow::All.bounds(now), None);
+        assert_eq!(
+            TimeWindow::Today.bounds(now),
+            Some((date("2025-10-22T00:00:00Z").timestamp(), end))
+        );
+        assert_eq!(
+            TimeWindow::ThisWeek.bounds(now),
+            Some((date("2025-10-20T00:00:00Z").timestamp(), end))
+        );
+        assert_eq!(
+            TimeWindow::Last7Days.bounds(now),
+            Some((date("2025-10-15T13:45:00Z").timestamp(), end))
+        );
+        assert_eq!(TimeWindow::Custom(1, 2).bounds(now), Some((1, 2)));
+        // On mondays, the week starts the same day
+        let monday = date("2025-10-20T08:00:00Z");
+        assert_eq!(
+            TimeWindow::ThisWeek.bounds(monday),
+            Some((
+                date("2025-10-20T00:00:00Z").timestamp(),
+                monday.timestamp() + 1
+            ))
+        );
+    }
+}
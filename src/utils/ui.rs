@@ -1,11 +1,27 @@
-use sea_orm::{DatabaseConnection, EntityTrait};
-use std::{cmp::min, collections::HashMap, usize};
+use chrono::Utc;
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter};
+use std::collections::HashMap;
 
-use serenity::all::{Builder, Context, CreateEmbed, EditMessage};
+use serenity::all::{
+    Builder, Context, CreateEmbed, CreateEmbedFooter, EditMessage, MessageBuilder,
+};
 
 use crate::{
-    CONFIG, Handler,
-    entities::{actions::ActionType, prelude::*},
+    CONFIG,
+    entities::{
+        action_participants,
+        actions::{self, ActionStatus, ActionType},
+        bounties, contestants, penalties,
+        prelude::*,
+    },
+    utils::{
+        contestants::{load_display_names, user_label},
+        participants::{load_participants, split_points},
+        snapshots::{RankMovements, load_snapshot},
+        streaks::{Streaks, compute_streaks},
+        teams::{TeamScore, compute_team_scores},
+        time::TimeWindow,
+    },
 };
 
 #[derive(Default, Clone, Copy)]
@@ -13,17 +29,267 @@ pub struct Score {
     bug_confirm: u64,
     bug_report: u64,
     pr_fix: u64,
+    points: i64,
+    /// Part of `points` from confirmed submissions
+    confirmed_points: i64,
+    /// Part of `points` gained from multiplier events
+    event_points: i64,
+    bounty: i64,
+    penalty: i64,
 }
 
 impl Score {
-    pub fn get_total_points(&self) -> u64 {
-        self.bug_confirm + 2 * self.bug_report + 5 * self.pr_fix
+    pub fn get_total_points(&self) -> i64 {
+        self.points + self.bounty - self.penalty
+    }
+
+    /// The value users are ranked by in a leaderboard category, the total points when `action_type` is unset
+    pub fn get_category_value(&self, action_type: Option<ActionType>) -> i64 {
+        match action_type {
+            None => self.get_total_points(),
+            Some(ActionType::ConfirmBug) => self.bug_confirm as i64,
+            Some(ActionType::ReportBug) => self.bug_report as i64,
+            Some(ActionType::PRFix) => self.pr_fix as i64,
+        }
+    }
+
+    /// Total points, without the base points of pending submissions
+    pub fn get_confirmed_points(&self) -> i64 {
+        self.confirmed_points + self.bounty - self.penalty
+    }
+
+    /// Every component of the score with its name, for exports
+    pub fn get_fields(&self) -> [(&'static str, i64); 8] {
+        [
+            ("bug_confirm", self.bug_confirm as i64),
+            ("bug_report", self.bug_report as i64),
+            ("pr_fix", self.pr_fix as i64),
+            ("total_points", self.get_total_points()),
+            ("confirmed_points", self.get_confirmed_points()),
+            ("event_points", self.event_points),
+            ("bounty", self.bounty),
+            ("penalty", self.penalty),
+        ]
+    }
+
+    /// The bonuses and penalties included in the total points, e.g. ` (+10 from bounties, -2 from penalties)`
+    pub fn get_points_breakdown(&self) -> String {
+        let parts: Vec<String> = [
+            (self.event_points, "from events"),
+            (self.bounty, "from bounties"),
+            (-self.penalty, "from penalties"),
+        ]
+        .iter()
+        .filter(|(points, _)| *points != 0)
+        .map(|(points, source)| format!("{points:+} {source}"))
+        .collect();
+        if parts.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", parts.join(", "))
+        }
+    }
+}
+
+/// Computes the score of every user from their submissions, collected bounties and penalties
+///
+/// Every participant of a shared submission counts it, but only gets their share of its points.
+/// Excluded contestants are left out
+pub async fn compute_scores(db_conn: &DatabaseConnection) -> Result<HashMap<String, Score>, DbErr> {
+    compute_scores_in(db_conn, None).await
+}
+
+/// Computes the scores like [`compute_scores`], only counting the submissions whose GitHub item
+/// was created between the `bounds`, and the bounties and penalties given between them
+pub async fn compute_scores_in(
+    db_conn: &DatabaseConnection,
+    bounds: Option<(i64, i64)>,
+) -> Result<HashMap<String, Score>, DbErr> {
+    Ok(ScoreRows::load(db_conn).await?.scores_in(bounds))
+}
+
+/// Everything scores are computed from, to compute them over several ranges with a single load
+pub struct ScoreRows {
+    actions: Vec<actions::Model>,
+    participants: HashMap<u32, Vec<action_participants::Model>>,
+    bounties: Vec<bounties::Model>,
+    penalties: Vec<penalties::Model>,
+    excluded: Vec<String>,
+}
+
+impl ScoreRows {
+    pub async fn load(db_conn: &DatabaseConnection) -> Result<Self, DbErr> {
+        Ok(ScoreRows {
+            actions: Actions::find().all(db_conn).await?,
+            participants: load_participants(db_conn).await?,
+            bounties: Bounties::find()
+                .filter(bounties::Column::ClaimedBy.is_not_null())
+                .all(db_conn)
+                .await?,
+            penalties: Penalties::find().all(db_conn).await?,
+            excluded: Contestants::find()
+                .filter(contestants::Column::Excluded.eq(true))
+                .all(db_conn)
+                .await?
+                .into_iter()
+                .map(|contestant| contestant.user_id)
+                .collect(),
+        })
+    }
+
+    /// The scores as [`compute_scores_in`] computes them
+    pub fn scores_in(&self, bounds: Option<(i64, i64)>) -> HashMap<String, Score> {
+        let in_bounds = |timestamp: Option<i64>| match (bounds, timestamp) {
+            (None, _) => true,
+            (Some((start, end)), Some(timestamp)) => start <= timestamp && timestamp < end,
+            (Some(_), None) => false,
+        };
+        let shares_of = |action_id: Option<u32>, user_id: &String| match action_id
+            .and_then(|id| self.participants.get(&id))
+        {
+            Some(participants) => participants
+                .iter()
+                .map(|participant| (participant.user_id.clone(), participant.share))
+                .collect(),
+            None => vec![(user_id.clone(), 1.0)],
+        };
+        // user_id: Score
+        let mut score_map: HashMap<String, Score> = HashMap::new();
+        for action in &self.actions {
+            if action.action_status == ActionStatus::Denied || !in_bounds(action.github_created_at)
+            {
+                continue;
+            }
+            // Pending submissions count for their base points until they are confirmed
+            let points = action
+                .points
+                .unwrap_or_else(|| action.action_type.get_points());
+            let event_points = action.event_points.unwrap_or(0);
+            let shares = shares_of(Some(action.id), &action.user_id);
+            let point_split = split_points(points, &action.user_id, &shares);
+            let event_split = split_points(event_points, &action.user_id, &shares);
+            for ((user_id, share_points), (_, share_event_points)) in
+                point_split.into_iter().zip(event_split)
+            {
+                let score = score_map.entry(user_id).or_default();
+                match action.action_type {
+                    ActionType::ReportBug => score.bug_report += 1,
+                    ActionType::ConfirmBug => score.bug_confirm += 1,
+                    ActionType::PRFix => score.pr_fix += 1,
+                };
+                score.points += share_points;
+                if action.action_status == ActionStatus::Confirmed {
+                    score.confirmed_points += share_points;
+                }
+                score.event_points += share_event_points;
+            }
+        }
+        for bounty in &self.bounties {
+            let Some(user_id) = &bounty.claimed_by else {
+                continue;
+            };
+            if !in_bounds(bounty.claimed_at) {
+                continue;
+            }
+            // Split like the points of the fix that claimed it
+            let shares = shares_of(bounty.claimed_action, user_id);
+            for (user_id, share_points) in split_points(bounty.points, user_id, &shares) {
+                score_map.entry(user_id).or_default().bounty += share_points;
+            }
+        }
+        for penalty in &self.penalties {
+            if !in_bounds(Some(penalty.created_at)) {
+                continue;
+            }
+            score_map
+                .entry(penalty.user_id.clone())
+                .or_default()
+                .penalty += penalty.points;
+        }
+        for user_id in &self.excluded {
+            score_map.remove(user_id);
+        }
+        score_map
+    }
+}
+
+/// Text picked by users, escaped so that it shows as typed instead of formatting the message
+/// or pinging anyone
+pub fn escape_markdown(text: &str) -> String {
+    let mut escaped = MessageBuilder::new()
+        .push_safe(text.replace('\\', "\\\\"))
+        .build();
+    for c in ['~', '|', '[', ']', '<', '>', '#'] {
+        escaped = escaped.replace(c, &format!("\\{c}"));
+    }
+    escaped
+}
+
+/// The leaderboard categories, in the order they are displayed
+pub const CATEGORIES: [Option<ActionType>; 4] = [
+    Some(ActionType::ConfirmBug),
+    Some(ActionType::ReportBug),
+    Some(ActionType::PRFix),
+    None,
+];
+
+/// Ranks the users with a positive value in a leaderboard category, tied users sharing a rank
+pub fn rank_users(
+    score_map: &HashMap<String, Score>,
+    action_type: Option<ActionType>,
+) -> Vec<(String, u32, i64)> {
+    let mut ranked: Vec<(&String, i64)> = score_map
+        .iter()
+        .map(|(user_id, score)| (user_id, score.get_category_value(action_type)))
+        .filter(|(_, value)| *value > 0)
+        .collect();
+    // Tied users are ordered by id, so that pages stay the same between requests
+    ranked.sort_by_key(|(user_id, value)| (std::cmp::Reverse(*value), *user_id));
+    let ranks = tied_ranks(&ranked.iter().map(|(_, value)| *value).collect::<Vec<_>>());
+    ranked
+        .into_iter()
+        .zip(ranks)
+        .map(|((user_id, value), rank)| (user_id.clone(), rank, value))
+        .collect()
+}
+
+/// The ranks of values sorted from best to worst, equal values sharing the rank of the first
+/// of them (1, 2, 2, 4)
+pub fn tied_ranks<T: PartialEq>(sorted_values: &[T]) -> Vec<u32> {
+    let mut rank = 0;
+    let mut ranks = Vec::with_capacity(sorted_values.len());
+    for (i, value) in sorted_values.iter().enumerate() {
+        if i == 0 || sorted_values[i - 1] != *value {
+            rank = i as u32 + 1;
+        }
+        ranks.push(rank);
+    }
+    ranks
+}
+
+/// Title of a leaderboard category, the points category when `action_type` is unset
+pub fn category_title(action_type: Option<ActionType>) -> &'static str {
+    match action_type {
+        None => "Points",
+        Some(ActionType::ConfirmBug) => "Bugs Confirmed",
+        Some(ActionType::ReportBug) => "Bugs Discovered",
+        Some(ActionType::PRFix) => "Bugs Solved",
+    }
+}
+
+/// What the values of a leaderboard category count, e.g. `confirmed bugs`
+pub fn category_unit(action_type: Option<ActionType>) -> &'static str {
+    match action_type {
+        None => "points",
+        Some(ActionType::ConfirmBug) => "confirmed bugs",
+        Some(ActionType::ReportBug) => "discovered bugs",
+        Some(ActionType::PRFix) => "solved bugs",
     }
 }
 
 pub async fn update_permanent_leaderboard(db_conn: &DatabaseConnection, ctx: &Context) {
     if let Err(err) = EditMessage::new()
-        .embed(generate_leaderboard_embed(db_conn, None, None).await)
+        .embed(generate_leaderboard_embed(db_conn, TimeWindow::All, None).await)
         .execute(
             &ctx.http,
             (
@@ -41,119 +307,530 @@ pub async fn update_permanent_leaderboard(db_conn: &DatabaseConnection, ctx: &Co
 
 pub async fn generate_leaderboard_embed(
     db_conn: &DatabaseConnection,
-    action_type: Option<ActionType>,
+    window: TimeWindow,
     id: Option<u64>,
 ) -> CreateEmbed {
-    let actions = Actions::find().all(db_conn).await;
-    // user_id: Score
-    let mut score_map: HashMap<String, Score> = HashMap::new();
-    for action in actions.unwrap() {
-        let score = score_map.entry(action.user_id).or_insert(Score::default());
-        match action.action_type {
-            ActionType::ReportBug => score.bug_report += 1,
-            ActionType::ConfirmBug => score.bug_confirm += 1,
-            ActionType::PRFix => score.pr_fix += 1,
-        };
-    }
+    let score_map = compute_scores_in(db_conn, window.bounds(Utc::now()))
+        .await
+        .unwrap();
 
-    let mut score_vec: Vec<_> = score_map.iter().collect();
+    let (display_names, movements) = load_decorations(db_conn, &score_map, window).await;
 
     CreateEmbed::new()
         .title("Bug Catching Contest 2025 Leaderboard")
-        .description(match action_type {
-            None => format!(
-                "\
-                    **Bugs Confirmed**{}\n\n\
-                    **Bugs Discovered**{}\n\n\
-                    **Bugs Solved**{}\n\n\
-                    **Points**{}",
-                generate_leaderboard_string(&mut score_vec, Some(ActionType::ConfirmBug), id, 5),
-                generate_leaderboard_string(&mut score_vec, Some(ActionType::ReportBug), id, 5),
-                generate_leaderboard_string(&mut score_vec, Some(ActionType::PRFix), id, 5),
-                generate_leaderboard_string(&mut score_vec, None, id, 5),
+        .description(format!(
+            "{}\
+                **Bugs Confirmed**{}\n\n\
+                **Bugs Discovered**{}\n\n\
+                **Bugs Solved**{}\n\n\
+                **Points**{}{}",
+            window_header(window),
+            generate_leaderboard_string(
+                &score_map,
+                &display_names,
+                &movements,
+                Some(ActionType::ConfirmBug),
+                id.map(Highlight::viewer),
+                0,
+                5
             ),
-            Some(t) => format!(
-                "**{}**{}",
-                match t {
-                    ActionType::ConfirmBug => "Bugs Confirmed",
-                    ActionType::ReportBug => "Bugs Discovered",
-                    ActionType::PRFix => "Bugs Solved",
-                },
-                generate_leaderboard_string(&mut score_vec, action_type, id, 20)
+            generate_leaderboard_string(
+                &score_map,
+                &display_names,
+                &movements,
+                Some(ActionType::ReportBug),
+                id.map(Highlight::viewer),
+                0,
+                5
             ),
-        })
+            generate_leaderboard_string(
+                &score_map,
+                &display_names,
+                &movements,
+                Some(ActionType::PRFix),
+                id.map(Highlight::viewer),
+                0,
+                5
+            ),
+            generate_leaderboard_string(
+                &score_map,
+                &display_names,
+                &movements,
+                None,
+                id.map(Highlight::viewer),
+                0,
+                5
+            ),
+            match compute_team_scores(db_conn, &score_map).await {
+                Ok(team_scores) if !team_scores.is_empty() => format!(
+                    "\n\n**Teams**{}",
+                    generate_team_leaderboard_string(&team_scores, id, 0, 5)
+                ),
+                Ok(_) => String::new(),
+                Err(e) => {
+                    log::error!("Error while computing team scores: {e:?}");
+                    String::new()
+                }
+            },
+        ))
 }
 
-fn generate_leaderboard_string(
-    score_vec: &mut Vec<(&String, &Score)>,
+/// The user whose lines are emphasized on a leaderboard
+#[derive(Clone, Copy)]
+pub struct Highlight {
+    pub user_id: u64,
+    /// Whether the user is the one looking at the leaderboard
+    pub is_viewer: bool,
+}
+
+impl Highlight {
+    pub fn viewer(user_id: u64) -> Self {
+        Highlight {
+            user_id,
+            is_viewer: true,
+        }
+    }
+
+    /// Closes the bold of a highlighted line
+    fn suffix(&self) -> &'static str {
+        if self.is_viewer { "** (You)" } else { "**" }
+    }
+}
+
+/// The ranks around a user in every category, `RADIUS` ranks above and below them
+pub async fn generate_user_standing_embed(
+    db_conn: &DatabaseConnection,
+    highlight: Highlight,
+) -> CreateEmbed {
+    const RADIUS: usize = 3;
+    let score_map = compute_scores(db_conn).await.unwrap();
+
+    let (display_names, movements) = load_decorations(db_conn, &score_map, TimeWindow::All).await;
+    let user_id = highlight.user_id.to_string();
+
+    let mut description = format!("Standing of <@{user_id}>");
+    for action_type in CATEGORIES {
+        let ranking = rank_users(&score_map, action_type);
+        description.push_str(&format!("\n\n**{}**", category_title(action_type)));
+        match ranking.iter().position(|(id, _, _)| *id == user_id) {
+            Some(pos) => description.push_str(&generate_leaderboard_string(
+                &score_map,
+                &display_names,
+                &movements,
+                action_type,
+                Some(highlight),
+                pos.saturating_sub(RADIUS),
+                2 * RADIUS + 1,
+            )),
+            None => description.push_str("\nNot ranked"),
+        }
+    }
+    CreateEmbed::new()
+        .title("Bug Catching Contest 2025 Leaderboard")
+        .description(description)
+}
+
+/// Number of users or teams on each page of the extended leaderboards
+const PAGE_SIZE: usize = 20;
+
+/// A page of an extended leaderboard
+pub struct LeaderboardPage {
+    pub embed: CreateEmbed,
+    /// Index of the page, from 0
+    pub page: usize,
+    pub page_count: usize,
+}
+
+/// The page index to show out of `page_count`, the one of `position` when `page` is unset
+fn select_page(page: Option<usize>, position: Option<usize>, page_count: usize) -> usize {
+    page.or(position.map(|position| position / PAGE_SIZE))
+        .unwrap_or(0)
+        .min(page_count - 1)
+}
+
+/// A page of the leaderboard of a single category, the page of the user `id` when `page` is unset
+pub async fn generate_category_page(
+    db_conn: &DatabaseConnection,
     action_type: Option<ActionType>,
+    window: TimeWindow,
+    page: Option<usize>,
     id: Option<u64>,
-    max: usize,
-) -> String {
-    let sort_fn = match action_type {
-        None => |k: (&String, &Score)| k.1.get_total_points(),
-        Some(ActionType::ConfirmBug) => |k: (&String, &Score)| k.1.bug_confirm,
-        Some(ActionType::ReportBug) => |k: (&String, &Score)| k.1.bug_report,
-        Some(ActionType::PRFix) => |k: (&String, &Score)| k.1.pr_fix,
+) -> LeaderboardPage {
+    let score_map = compute_scores_in(db_conn, window.bounds(Utc::now()))
+        .await
+        .unwrap();
+
+    let (display_names, movements) = load_decorations(db_conn, &score_map, window).await;
+    let ranking = rank_users(&score_map, action_type);
+    let page_count = ranking.len().div_ceil(PAGE_SIZE).max(1);
+    let position = id.and_then(|id| ranking.iter().position(|(u, _, _)| *u == id.to_string()));
+    let page = select_page(page, position, page_count);
+
+    let embed = CreateEmbed::new()
+        .title("Bug Catching Contest 2025 Leaderboard")
+        .description(format!(
+            "{}**{}**{}",
+            window_header(window),
+            category_title(action_type),
+            generate_leaderboard_string(
+                &score_map,
+                &display_names,
+                &movements,
+                action_type,
+                id.map(Highlight::viewer),
+                page * PAGE_SIZE,
+                PAGE_SIZE
+            )
+        ))
+        .footer(CreateEmbedFooter::new(format!(
+            "Page {}/{page_count}",
+            page + 1
+        )));
+    LeaderboardPage {
+        embed,
+        page,
+        page_count,
+    }
+}
+
+/// A page of the streak leaderboard, by current then longest daily streak,
+/// the page of the user `id` when `page` is unset
+pub async fn generate_streak_page(
+    db_conn: &DatabaseConnection,
+    window: TimeWindow,
+    page: Option<usize>,
+    id: Option<u64>,
+) -> LeaderboardPage {
+    let streaks = compute_streaks(db_conn).await.unwrap();
+    let display_names = load_display_names(db_conn).await.unwrap_or_else(|e| {
+        log::error!("Error while fetching contestant display names: {e:?}");
+        HashMap::new()
+    });
+
+    let mut streak_vec: Vec<_> = streaks.iter().collect();
+    streak_vec.sort_by_key(|(user_id, s)| {
+        (
+            std::cmp::Reverse(s.current_daily),
+            std::cmp::Reverse(s.longest_daily),
+            *user_id,
+        )
+    });
+    let page_count = streak_vec.len().div_ceil(PAGE_SIZE).max(1);
+    let user_id = id.map(|id| id.to_string());
+    let position = streak_vec
+        .iter()
+        .position(|(u, _)| Some(*u) == user_id.as_ref());
+    let page = select_page(page, position, page_count);
+    let ranks = tied_ranks(
+        &streak_vec
+            .iter()
+            .map(|(_, s)| (s.current_daily, s.longest_daily))
+            .collect::<Vec<_>>(),
+    );
+
+    let line = |i: usize, (u, s): (&String, &Streaks)| {
+        format!(
+            "#{} {}: {} day{} (longest {}), {} week{} (longest {})",
+            ranks[i],
+            user_label(&display_names, u),
+            s.current_daily,
+            if s.current_daily == 1 { "" } else { "s" },
+            s.longest_daily,
+            s.current_weekly,
+            if s.current_weekly == 1 { "" } else { "s" },
+            s.longest_weekly,
+        )
     };
-    let type_str = match action_type {
-        None => "points",
-        Some(ActionType::ConfirmBug) => "confirmed bugs",
-        Some(ActionType::ReportBug) => "discovered bugs",
-        Some(ActionType::PRFix) => "solved bugs",
+    let mut description = match window {
+        // Streaks are only meaningful over the whole contest
+        TimeWindow::All => "**Streaks**".to_string(),
+        _ => "*Streaks always span the whole contest*\n\n**Streaks**".to_string(),
     };
-    score_vec.sort_by_key(|k: &(&String, &Score)| sort_fn(*k));
-    score_vec.reverse();
+    if streak_vec.is_empty() {
+        description.push_str("\nNo streak yet");
+    }
+    for (i, (u, s)) in streak_vec
+        .iter()
+        .enumerate()
+        .skip(page * PAGE_SIZE)
+        .take(PAGE_SIZE)
+    {
+        if position == Some(i) {
+            description.push_str(&format!("\n**{}** (You)", line(i, (u, s))));
+        } else {
+            description.push_str(&format!("\n{}", line(i, (u, s))));
+        }
+    }
+    if let Some(pos) = position
+        && !(page * PAGE_SIZE..(page + 1) * PAGE_SIZE).contains(&pos)
+    {
+        description.push_str(&format!("\n**{}** (You)", line(pos, streak_vec[pos])));
+    }
 
-    let mut res_str = String::new();
+    let embed = CreateEmbed::new()
+        .title("Bug Catching Contest 2025 Leaderboard")
+        .description(description)
+        .footer(CreateEmbedFooter::new(format!(
+            "Page {}/{page_count}",
+            page + 1
+        )));
+    LeaderboardPage {
+        embed,
+        page,
+        page_count,
+    }
+}
 
-    let mut display_user = match id {
-        None => false,
-        Some(_) => true,
+/// The display names and rank movements shown next to users on a leaderboard
+async fn load_decorations(
+    db_conn: &DatabaseConnection,
+    score_map: &HashMap<String, Score>,
+    window: TimeWindow,
+) -> (HashMap<String, String>, RankMovements) {
+    let display_names = load_display_names(db_conn).await.unwrap_or_else(|e| {
+        log::error!("Error while fetching contestant display names: {e:?}");
+        HashMap::new()
+    });
+    // Snapshots are taken over the whole contest, so movements are meaningless in other windows
+    let movements = match window {
+        TimeWindow::All => RankMovements::load(db_conn, score_map)
+            .await
+            .unwrap_or_else(|e| {
+                log::error!("Error while loading rank movements: {e:?}");
+                RankMovements::default()
+            }),
+        _ => RankMovements::default(),
     };
-    for i in 0..min(max, score_vec.len()) {
-        let u = score_vec[i];
+    (display_names, movements)
+}
 
-        let current_user = display_user && (*u.0 == id.unwrap().to_string());
-        if current_user {
-            display_user = false;
-        }
-        let prefix = match i {
-            0 => "🥇".to_string(),
-            1 => "🥈".to_string(),
-            2 => "🥉".to_string(),
-            _ => format!("#{}", i + 1),
-        };
-        res_str.push_str(
-            &format!(
-                "\n{}{} <@{}>: {} {}{}",
+/// Tells which period a leaderboard is restricted to, empty for the whole contest
+fn window_header(window: TimeWindow) -> String {
+    match window {
+        TimeWindow::All => String::new(),
+        _ => format!("*{}*\n\n", window.label()),
+    }
+}
+
+/// The leaderboard as it was in the latest snapshot taken at or before `as_of`
+pub async fn generate_snapshot_embed(
+    db_conn: &DatabaseConnection,
+    as_of: i64,
+    highlight: Option<Highlight>,
+) -> CreateEmbed {
+    let snapshot = load_snapshot(db_conn, as_of).await.unwrap_or_else(|e| {
+        log::error!("Error while fetching leaderboard snapshot: {e:?}");
+        vec![]
+    });
+    let Some(taken_at) = snapshot.first().map(|row| row.taken_at) else {
+        return CreateEmbed::new()
+            .title("Bug Catching Contest 2025 Leaderboard")
+            .description(format!("No leaderboard snapshot before <t:{as_of}:f>"));
+    };
+    let display_names = load_display_names(db_conn).await.unwrap_or_else(|e| {
+        log::error!("Error while fetching contestant display names: {e:?}");
+        HashMap::new()
+    });
+    let user_id = highlight.map(|h| h.user_id.to_string());
+
+    let mut description = format!("Standings as of <t:{taken_at}:f>");
+    for action_type in CATEGORIES {
+        description.push_str(&format!("\n\n**{}**", category_title(action_type)));
+        let rows: Vec<_> = snapshot
+            .iter()
+            .filter(|row| row.action_type == action_type)
+            .collect();
+        for (i, row) in rows.iter().enumerate() {
+            let current_user = user_id.as_ref() == Some(&row.user_id);
+            if i >= 5 && !current_user {
+                continue;
+            }
+            let prefix = match row.rank {
+                1 => "🥇".to_string(),
+                2 => "🥈".to_string(),
+                3 => "🥉".to_string(),
+                rank => format!("#{rank}"),
+            };
+            description.push_str(&format!(
+                "\n{}{} {}: {} {}{}",
                 if current_user { "**" } else { "" },
                 prefix,
-                u.0,
-                sort_fn(u),
-                type_str,
-                if current_user { "** (You)" } else { "" },
-            )
-            .to_owned(),
-        );
+                user_label(&display_names, &row.user_id),
+                row.value,
+                category_unit(action_type),
+                match highlight {
+                    Some(highlight) if current_user => highlight.suffix(),
+                    _ => "",
+                },
+            ));
+        }
     }
-    if display_user {
-        if let Some(pos) = score_vec
+    CreateEmbed::new()
+        .title("Bug Catching Contest 2025 Leaderboard")
+        .description(description)
+}
+
+/// A page of the team leaderboard, the page of the team of the user `id` when `page` is unset
+pub async fn generate_team_page(
+    db_conn: &DatabaseConnection,
+    window: TimeWindow,
+    page: Option<usize>,
+    id: Option<u64>,
+) -> LeaderboardPage {
+    let score_map = compute_scores_in(db_conn, window.bounds(Utc::now()))
+        .await
+        .unwrap();
+    let team_scores = compute_team_scores(db_conn, &score_map).await.unwrap();
+    let page_count = team_scores.len().div_ceil(PAGE_SIZE).max(1);
+    let page = select_page(page, user_team_position(&team_scores, id), page_count);
+
+    let embed = CreateEmbed::new()
+        .title("Bug Catching Contest 2025 Leaderboard")
+        .description(
+            window_header(window)
+                + &if team_scores.is_empty() {
+                    "**Teams**\nNo team yet, create one with `/team create` !".to_string()
+                } else {
+                    format!(
+                        "**Teams**{}",
+                        generate_team_leaderboard_string(
+                            &team_scores,
+                            id,
+                            page * PAGE_SIZE,
+                            PAGE_SIZE
+                        )
+                    )
+                },
+        )
+        .footer(CreateEmbedFooter::new(format!(
+            "Page {}/{page_count}",
+            page + 1
+        )));
+    LeaderboardPage {
+        embed,
+        page,
+        page_count,
+    }
+}
+
+fn user_team_position(team_scores: &[TeamScore], id: Option<u64>) -> Option<usize> {
+    id.and_then(|id| {
+        team_scores
             .iter()
-            .position(|u| *u.0 == id.unwrap().to_string())
-        {
-            let u = score_vec[pos];
-            res_str.push_str(
-                &format!(
-                    "\n**{} <@{}>: {} {}** (You)",
-                    format!("#{}", pos + 1),
-                    u.0,
-                    sort_fn(u),
-                    type_str,
-                )
-                .to_owned(),
-            );
+            .position(|t| t.members.contains(&id.to_string()))
+    })
+}
+
+fn generate_team_leaderboard_string(
+    team_scores: &[TeamScore],
+    id: Option<u64>,
+    start: usize,
+    max: usize,
+) -> String {
+    let user_team = user_team_position(team_scores, id);
+    let ranks = tied_ranks(&team_scores.iter().map(|t| t.points).collect::<Vec<_>>());
+    let line = |i: usize, t: &TeamScore| {
+        format!(
+            "{} **{}**: {} points ({} member{})",
+            match ranks[i] {
+                1 => "🥇".to_string(),
+                2 => "🥈".to_string(),
+                3 => "🥉".to_string(),
+                rank => format!("#{rank}"),
+            },
+            escape_markdown(&t.team.name),
+            t.points,
+            t.members.len(),
+            if t.members.len() == 1 { "" } else { "s" },
+        )
+    };
+
+    let mut res_str = String::new();
+    for (i, t) in team_scores.iter().enumerate().skip(start).take(max) {
+        if user_team == Some(i) {
+            res_str.push_str(&format!("\n__{}__ (Your team)", line(i, t)));
+        } else {
+            res_str.push_str(&format!("\n{}", line(i, t)));
         }
     }
+    if let Some(pos) = user_team
+        && !(start..start + max).contains(&pos)
+    {
+        res_str.push_str(&format!(
+            "\n__{}__ (Your team)",
+            line(pos, &team_scores[pos])
+        ));
+    }
     res_str
 }
+
+/// The lines of a leaderboard category, with the users ranked by [`rank_users`]
+fn generate_leaderboard_string(
+    score_map: &HashMap<String, Score>,
+    display_names: &HashMap<String, String>,
+    movements: &RankMovements,
+    action_type: Option<ActionType>,
+    highlight: Option<Highlight>,
+    start: usize,
+    max: usize,
+) -> String {
+    let type_str = category_unit(action_type);
+    let breakdown = |user_id: &String| match (action_type, score_map.get(user_id)) {
+        (None, Some(score)) => score.get_points_breakdown(),
+        _ => String::new(),
+    };
+    let line = |(user_id, rank, value): &(String, u32, i64)| {
+        let prefix = match rank {
+            1 => "🥇".to_string(),
+            2 => "🥈".to_string(),
+            3 => "🥉".to_string(),
+            _ => format!("#{rank}"),
+        };
+        format!(
+            "{prefix} {}: {value} {type_str}{}{}",
+            user_label(display_names, user_id),
+            breakdown(user_id),
+            movements.marker(action_type, user_id),
+        )
+    };
+
+    let ranking = rank_users(score_map, action_type);
+    let mut res_str = String::new();
+
+    let highlighted_id = highlight.map(|h| h.user_id.to_string());
+    let mut display_user = highlight.is_some();
+    for ranked in ranking.iter().skip(start).take(max) {
+        let current_user = display_user && Some(&ranked.0) == highlighted_id.as_ref();
+        match highlight {
+            Some(highlight) if current_user => {
+                display_user = false;
+                res_str.push_str(&format!("\n**{}{}", line(ranked), highlight.suffix()));
+            }
+            _ => res_str.push_str(&format!("\n{}", line(ranked))),
+        }
+    }
+    if let Some(highlight) = highlight
+        && display_user
+        && let Some(ranked) = ranking
+            .iter()
+            .find(|(user_id, _, _)| Some(user_id) == highlighted_id.as_ref())
+    {
+        res_str.push_str(&format!("\n**{}{}", line(ranked), highlight.suffix()));
+    }
+    res_str
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ties_share_a_rank() {
+        assert_eq!(
+            tied_ranks(&[10, 8, 8, 5, 5, 5, 1]),
+            vec![1, 2, 2, 4, 4, 4, 7]
+        );
+        assert_eq!(tied_ranks(&[3, 3]), vec![1, 1]);
+        assert_eq!(tied_ranks::<i64>(&[]), Vec::<u32>::new());
+    }
+}
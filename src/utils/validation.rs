@@ -0,0 +1,442 @@
+use octocrab::models::Event;
+use octocrab::models::issues::{Comment, Issue};
+
+use crate::CONFIG;
+use crate::entities::actions::ActionType;
+use crate::utils::config::{EligibilityRule, RuleSubject};
+use crate::utils::issues::{REPO_NAME, REPO_OWNER, closing_references};
+
+/// Checks that `comment` is a genuine confirmation of the bug reported in `issue`,
+/// returning the reason to show to the user otherwise
+pub fn validate_confirmation(issue: &Issue, comment: &Comment) -> Result<(), String> {
+    let on_issue = comment
+        .issue_url
+        .as_ref()
+        .is_some_and(|url| url.path().ends_with(&format!("/issues/{}", issue.number)));
+    if !on_issue {
+        return Err(format!(
+            "The submitted comment wasn't posted on issue #{}",
+            issue.number
+        ));
+    }
+
+    if comment.user.id == issue.user.id {
+        return Err(format!(
+            "The submitted comment was written by the author of the bug report ({}), a bug has to be confirmed by someone else",
+            issue.user.login
+        ));
+    }
+
+    if comment.created_at <= issue.created_at {
+        return Err(format!(
+            "The submitted comment was posted <t:{}:R>, before the bug was reported <t:{}:R>",
+            comment.created_at.timestamp(),
+            issue.created_at.timestamp()
+        ));
+    }
+
+    if !matches_keywords(
+        comment.body.as_deref().unwrap_or_default(),
+        &CONFIG.confirmation_keywords,
+    ) {
+        return Err(format!(
+            "The submitted comment doesn't look like a bug confirmation, it should contain one of: {}",
+            CONFIG
+                .confirmation_keywords
+                .iter()
+                .map(|keyword| format!("`{keyword}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    Ok(())
+}
+
+/// Whether `body` contains one of the keywords, ignoring case. Anything matches when
+/// no keyword is configured
+fn matches_keywords(body: &str, keywords: &[String]) -> bool {
+    let body = body.to_lowercase();
+    keywords.is_empty()
+        || keywords
+            .iter()
+            .any(|keyword| body.contains(&keyword.to_lowercase()))
+}
+
+impl EligibilityRule {
+    /// Checks the labels of an issue against this rule
+    pub fn check_labels(&self, labels: &[String]) -> Result<(), String> {
+        if let Some(missing) = self
+            .required_labels
+            .iter()
+            .find(|required| !labels.contains(required))
+        {
+            return Err(format!("missing the `{missing}` label"));
+        }
+        if let Some(forbidden) = self
+            .forbidden_labels
+            .iter()
+            .find(|forbidden| labels.contains(forbidden))
+        {
+            return Err(format!("labelled `{forbidden}`"));
+        }
+        if !self.any_of_labels.is_empty()
+            && !self
+                .any_of_labels
+                .iter()
+                .any(|label| labels.contains(label))
+        {
+            return Err(format!(
+                "missing one of the {} labels",
+                self.any_of_labels
+                    .iter()
+                    .map(|label| format!("`{label}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks `issue` against this rule, `author` being the login of whoever wrote the submitted item
+    pub fn check(
+        &self,
+        issue: &Issue,
+        author: &str,
+        submitter: Option<&str>,
+    ) -> Result<(), String> {
+        let labels: Vec<String> = issue
+            .labels
+            .iter()
+            .map(|label| label.name.clone())
+            .collect();
+        self.check_labels(&labels)?;
+        if !self.states.is_empty() && !self.states.contains(&issue.state) {
+            return Err(format!("#{} is {:?}", issue.number, issue.state).to_lowercase());
+        }
+        if self
+            .excluded_authors
+            .iter()
+            .any(|excluded| excluded.eq_ignore_ascii_case(author))
+        {
+            return Err(format!("items written by {author} aren't eligible"));
+        }
+        if self.author_must_be_submitter {
+            match submitter {
+                None => {
+                    return Err(
+                        "your GitHub account must be linked with `/link` to submit".to_string()
+                    );
+                }
+                Some(submitter) if !submitter.eq_ignore_ascii_case(author) => {
+                    return Err(format!("it was written by {author}, not by you"));
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The configured rules for a submission type and subject
+pub fn rules_for(
+    action_type: ActionType,
+    subject: RuleSubject,
+) -> impl Iterator<Item = &'static EligibilityRule> {
+    CONFIG
+        .eligibility_rules
+        .iter()
+        .filter(move |rule| rule.subject == subject && rule.action_types.contains(&action_type))
+}
+
+/// Checks `issue` against every rule configured for the submission type and subject,
+/// returning which rule failed and why otherwise
+pub fn check_eligibility(
+    action_type: ActionType,
+    subject: RuleSubject,
+    issue: &Issue,
+    author: &str,
+    submitter: Option<&str>,
+) -> Result<(), String> {
+    for rule in rules_for(action_type, subject) {
+        if let Err(reason) = rule.check(issue, author, submitter) {
+            return Err(format!("Rule **{}** failed: {reason}", rule.name));
+        }
+    }
+    Ok(())
+}
+
+/// Finds the bug issue a PR fixes, either through a closing keyword in its body
+/// or through an issue of the repository referencing it.
+/// The linked issue must pass the [`RuleSubject::LinkedIssue`] rules of bugfix PRs.
+pub async fn find_linked_bug(
+    pr: &Issue,
+    submitter: Option<&str>,
+) -> octocrab::Result<Result<Issue, String>> {
+    let mut candidates = closing_references(pr.body.as_deref().unwrap_or_default());
+
+    let first_page = octocrab::instance()
+        .issues(REPO_OWNER, REPO_NAME)
+        .list_timeline_events(pr.number)
+        .per_page(100)
+        .send()
+        .await?;
+    // The referencing event can be far down the timeline of a busy PR
+    let events = octocrab::instance().all_pages(first_page).await?;
+    for event in events {
+        if event.event != Event::CrossReferenced {
+            continue;
+        }
+        if let Some(source) = event.source
+            && source.issue.repository_url == pr.repository_url
+            && !candidates.contains(&source.issue.number)
+        {
+            candidates.push(source.issue.number);
+        }
+    }
+
+    let mut first_failure = None;
+    for number in candidates {
+        let issue = match octocrab::instance()
+            .issues(REPO_OWNER, REPO_NAME)
+            .get(number)
+            .await
+        {
+            Ok(issue) => issue,
+            Err(e) => {
+                log::debug!(
+                    "Error while fetching issue #{number} linked to PR #{}: {e:?}",
+                    pr.number
+                );
+                continue;
+            }
+        };
+        if issue.pull_request.is_some() {
+            continue;
+        }
+        match check_eligibility(
+            ActionType::PRFix,
+            RuleSubject::LinkedIssue,
+            &issue,
+            &issue.user.login,
+            submitter,
+        ) {
+            Ok(()) => return Ok(Ok(issue)),
+            Err(reason) => {
+                first_failure.get_or_insert(format!("#{number}: {reason}"));
+            }
+        }
+    }
+    Ok(Err(first_failure.unwrap_or_else(|| {
+        "Add a closing keyword to its description (e.g. `Fixes #123`) or reference it from the bug report".to_string()
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use octocrab::models::IssueState;
+    use serde_json::{Value, json};
+
+    use super::*;
+
+    const API: &str = "https://api.github.com/repos/rh-hideout/pokeemerald-expansion";
+
+    fn author(id: u64, login: &str) -> Value {
+        let url = format!("https://api.github.com/users/{login}");
+        json!({
+            "login": login,
+            "id": id,
+            "node_id": "",
+            "avatar_url": url,
+            "gravatar_id": "",
+            "url": url,
+            "html_url": url,
+            "followers_url": url,
+            "following_url": url,
+            "gists_url": url,
+            "starred_url": url,
+            "subscriptions_url": url,
+            "organizations_url": url,
+            "repos_url": url,
+            "events_url": url,
+            "received_events_url": url,
+            "type": "User",
+            "site_admin": false,
+            "name": null,
+            "patch_url": null,
+        })
+    }
+
+    fn issue(number: u64, user: (u64, &str), created_at: &str, labels: &[&str]) -> Issue {
+        let url = format!("{API}/issues/{number}");
+        let labels: Vec<Value> = labels
+            .iter()
+            .enumerate()
+            .map(|(id, name)| {
+                json!({
+                    "id": id,
+                    "node_id": "",
+                    "url": format!("{API}/labels/{name}"),
+                    "name": name,
+                    "color": "ffffff",
+                    "default": false,
+                })
+            })
+            .collect();
+        serde_json::from_value(json!({
+            "id": number,
+            "node_id": "",
+            "url": url,
+            "repository_url": API,
+            "labels_url": url,
+            "comments_url": url,
+            "events_url": url,
+            "html_url": url,
+            "number": number,
+            "state": "open",
+            "state_reason": null,
+            "title": "Bug",
+            "body": null,
+            "user": author(user.0, user.1),
+            "labels": labels,
+            "assignees": [],
+            "author_association": "NONE",
+            "locked": false,
+            "comments": 0,
+            "created_at": created_at,
+            "updated_at": created_at,
+        }))
+        .unwrap()
+    }
+
+    fn comment(issue_number: u64, user: (u64, &str), created_at: &str, body: &str) -> Comment {
+        let url = format!("{API}/issues/comments/1");
+        serde_json::from_value(json!({
+            "id": 1,
+            "node_id": "",
+            "url": url,
+            "html_url": url,
+            "issue_url": format!("{API}/issues/{issue_number}"),
+            "body": body,
+            "author_association": "NONE",
+            "user": author(user.0, user.1),
+            "created_at": created_at,
+        }))
+        .unwrap()
+    }
+
+    const REPORTER: (u64, &str) = (1, "reporter");
+    const CONFIRMER: (u64, &str) = (2, "confirmer");
+    const REPORTED_AT: &str = "2025-10-10T12:00:00Z";
+    const CONFIRMED_AT: &str = "2025-10-11T12:00:00Z";
+
+    #[test]
+    fn accepts_confirmations_from_someone_else() {
+        let bug = issue(12, REPORTER, REPORTED_AT, &["bug"]);
+        let confirmation = comment(12, CONFIRMER, CONFIRMED_AT, "I can reproduce this");
+        assert_eq!(validate_confirmation(&bug, &confirmation), Ok(()));
+    }
+
+    #[test]
+    fn rejects_invalid_confirmations() {
+        let bug = issue(12, REPORTER, REPORTED_AT, &["bug"]);
+        // Posted on another issue
+        assert!(validate_confirmation(&bug, &comment(13, CONFIRMER, CONFIRMED_AT, "")).is_err());
+        // Written by the reporter
+        assert!(validate_confirmation(&bug, &comment(12, REPORTER, CONFIRMED_AT, "")).is_err());
+        // Posted before the report
+        assert!(
+            validate_confirmation(&bug, &comment(12, CONFIRMER, "2025-10-09T12:00:00Z", ""))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn matches_keywords_ignoring_case() {
+        let keywords = vec!["Reproduced".to_string(), "can confirm".to_string()];
+        assert!(matches_keywords("I reproduced it on master", &keywords));
+        assert!(matches_keywords(
+            "CAN CONFIRM, happens to me too",
+            &keywords
+        ));
+        assert!(!matches_keywords("Works for me", &keywords));
+        assert!(!matches_keywords("", &keywords));
+        assert!(matches_keywords("Anything", &[]));
+    }
+
+    fn base_rule() -> EligibilityRule {
+        EligibilityRule {
+            name: "Test rule".to_string(),
+            action_types: vec![ActionType::ReportBug],
+            subject: RuleSubject::Issue,
+            required_labels: vec![],
+            forbidden_labels: vec![],
+            any_of_labels: vec![],
+            states: vec![],
+            excluded_authors: vec![],
+            author_must_be_submitter: false,
+        }
+    }
+
+    fn labels(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn checks_required_and_forbidden_labels() {
+        let rule = EligibilityRule {
+            required_labels: labels(&["bug", "confirmed"]),
+            forbidden_labels: labels(&["duplicate"]),
+            ..base_rule()
+        };
+        assert_eq!(rule.check_labels(&labels(&["confirmed", "bug"])), Ok(()));
+        assert!(rule.check_labels(&labels(&["bug"])).is_err());
+        assert!(
+            rule.check_labels(&labels(&["bug", "confirmed", "duplicate"]))
+                .is_err()
+        );
+        assert!(rule.check_labels(&[]).is_err());
+    }
+
+    #[test]
+    fn checks_any_of_labels() {
+        let rule = EligibilityRule {
+            any_of_labels: labels(&["battle", "overworld"]),
+            ..base_rule()
+        };
+        assert_eq!(rule.check_labels(&labels(&["bug", "overworld"])), Ok(()));
+        assert!(rule.check_labels(&labels(&["bug"])).is_err());
+        assert_eq!(base_rule().check_labels(&[]), Ok(()));
+    }
+
+    #[test]
+    fn checks_states_and_authors() {
+        let bug = issue(12, REPORTER, REPORTED_AT, &["bug"]);
+        assert_eq!(base_rule().check(&bug, "reporter", None), Ok(()));
+
+        let closed_only = EligibilityRule {
+            states: vec![IssueState::Closed],
+            ..base_rule()
+        };
+        assert!(closed_only.check(&bug, "reporter", None).is_err());
+
+        let no_bots = EligibilityRule {
+            excluded_authors: labels(&["Dependabot"]),
+            ..base_rule()
+        };
+        assert!(no_bots.check(&bug, "dependabot", None).is_err());
+        assert_eq!(no_bots.check(&bug, "reporter", None), Ok(()));
+
+        let own_items = EligibilityRule {
+            author_must_be_submitter: true,
+            ..base_rule()
+        };
+        assert!(own_items.check(&bug, "reporter", None).is_err());
+        assert!(
+            own_items
+                .check(&bug, "reporter", Some("confirmer"))
+                .is_err()
+        );
+        assert_eq!(own_items.check(&bug, "reporter", Some("Reporter")), Ok(()));
+    }
+}